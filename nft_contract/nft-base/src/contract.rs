@@ -1,30 +1,45 @@
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
-use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, CustomMsg};
+use cosmwasm_std::{
+    to_binary, Addr, Api, Binary, BlockInfo, Decimal, Deps, DepsMut, Empty, Env, MessageInfo, Order, Response, CustomMsg, StdResult,
+    Storage,
+};
 
 use cw2::set_contract_version;
 use cw721::{Cw721Execute, Cw721ReceiveMsg, Expiration};
+use cw_storage_plus::{Bound, Map};
 use url::Url;
 
 use crate::error::ContractError;
 use crate::msg::{
-    ContractInfoResponse, CreateShoeModelMsg, ExecuteMsg, InstantiateMsg, MintMsg,
+    BatchMintMsg, BatchMintResponse, ContractInfoResponse, CreateShoeModelMsg, ExecuteMsg,
+    InstantiateMsg, MintMsg, MintResponse,
     RoyaltyInfoResponse,
 };
 use crate::state::{
-    NFTContract, Approval, CollectionInfo, ModelInfo, RoyaltyInfo, TokenInfo,
-    COLLECTION_INFO,
+    NFTContract, Approval, CollectionInfo, MetadataExtension, ModelInfo, RoyaltyInfo,
+    collection_royalty, FrozenToken, MinterQuota, PendingRoyalty, RoyaltyIncreaseWindow, RoyaltyRecipient,
+    RoyaltyTimelock,
+    SizeSupply, TokenInfo, Trait, COLLECTION_INFO, COLLECTION_OWNER, LAST_TOKEN_ID,
+    METADATA_FROZEN, MINTERS, PENDING_COLLECTION_OWNER, PENDING_MINTER, PENDING_ROYALTY,
+    Redemption, RedemptionStatus, FROZEN_TOKENS, REDEEMER, REDEMPTIONS, ROYALTY_INCREASE_WINDOW,
+    ROYALTY_TIMELOCK,
+    MODEL_SUPPLY, MODEL_TRAITS, SIZE_SUPPLY, TOKEN_TRAITS,
 };
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:anone-cw721";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 const MAX_DESCRIPTION_LENGTH: u32 = 512;
+const DEFAULT_PROPAGATE_LIMIT: u32 = 30;
+const MAX_PROPAGATE_LIMIT: u32 = 100;
+const MAX_BATCH_SIZE: u32 = 100;
+const MAX_FREEZE_REASON_LENGTH: usize = 256;
 
 impl<'a, T, C> NFTContract<'a, T, C>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Serialize + DeserializeOwned + Clone + MetadataExtension,
     C: CustomMsg,
 {
     pub fn instantiate(
@@ -57,15 +72,11 @@ where
             Url::parse(external_link)?;
         }
 
-        let royalty_info: Option<RoyaltyInfo> = match msg.collection_info.royalty_info {
-            Some(royalty_info) => Some(RoyaltyInfo {
-                payment_address: deps.api.addr_validate(&royalty_info.payment_address)?,
-                share: royalty_info.share_validate()?,
-            }),
-            None => None,
-        };
+        let royalty_info = parse_royalty_info(deps.api, msg.collection_info.royalty_info)?;
 
-        deps.api.addr_validate(&msg.collection_info.creator)?;
+        // the creator owns the collection until ownership is transferred
+        let owner = deps.api.addr_validate(&msg.collection_info.creator)?;
+        COLLECTION_OWNER.save(deps.storage, &owner)?;
 
         let collection_info = CollectionInfo {
             creator: msg.collection_info.creator,
@@ -75,7 +86,11 @@ where
             royalty_info,
         };
 
+        let royalty_timelock = msg.royalty_timelock.unwrap_or_default();
+        validate_royalty_timelock(&royalty_timelock)?;
+
         COLLECTION_INFO.save(deps.storage, &collection_info)?;
+        ROYALTY_TIMELOCK.save(deps.storage, &royalty_timelock)?;
 
         Ok(Response::default()
             .add_attribute("action", "instantiate")
@@ -93,6 +108,11 @@ where
     ) -> Result<Response<C>, ContractError> {
         match msg {
             ExecuteMsg::Mint(msg) => self.mint(deps, env, info, msg),
+            ExecuteMsg::BatchMint(msg) => self.batch_mint(deps, env, info, msg),
+            ExecuteMsg::BatchTransfer {
+                recipient,
+                token_ids,
+            } => self.batch_transfer(deps, env, info, recipient, token_ids),
             ExecuteMsg::CreateShoeModel(msg) => self.create_model(deps, env, info, msg),
             ExecuteMsg::Approve {
                 spender,
@@ -116,6 +136,24 @@ where
                 msg,
             } => self.send_nft(deps, env, info, contract, token_id, msg),
             ExecuteMsg::Burn { token_id } => self.burn(deps, env, info, token_id),
+            ExecuteMsg::FreezeToken { token_id, reason } => {
+                self.freeze_token(deps, env, info, token_id, reason)
+            }
+            ExecuteMsg::UnfreezeToken { token_id } => self.unfreeze_token(deps, env, info, token_id),
+            ExecuteMsg::RequestRedemption { token_id } => {
+                self.request_redemption(deps, env, info, token_id)
+            }
+            ExecuteMsg::CancelRedemption { token_id } => {
+                self.cancel_redemption(deps, env, info, token_id)
+            }
+            ExecuteMsg::ConfirmRedemption { token_id, burn } => {
+                self.confirm_redemption(deps, env, info, token_id, burn)
+            }
+            ExecuteMsg::SetRedeemer { address } => self.set_redeemer(deps, env, info, address),
+            ExecuteMsg::RecallNft {
+                token_id,
+                recipient,
+            } => self.recall_nft(deps, env, info, token_id, recipient),
             ExecuteMsg::ModifyCollectionInfo {
                 description,
                 image,
@@ -130,6 +168,50 @@ where
                 external_link,
                 royalty_info,
             ),
+            ExecuteMsg::UpdateMinter { new_minter } => {
+                self.update_minter(deps, env, info, new_minter)
+            }
+            ExecuteMsg::AcceptMinter {} => self.accept_minter(deps, env, info),
+            ExecuteMsg::RenounceMinter {} => self.renounce_minter(deps, env, info),
+            ExecuteMsg::AddMinter { address, quota } => {
+                self.add_minter(deps, env, info, address, quota)
+            }
+            ExecuteMsg::RemoveMinter { address } => self.remove_minter(deps, env, info, address),
+            ExecuteMsg::UpdateModel {
+                model_id,
+                owner,
+                extension,
+            } => self.update_model(deps, env, info, model_id, owner, extension),
+            ExecuteMsg::TransferModel {
+                model_id,
+                recipient,
+            } => self.transfer_model(deps, env, info, model_id, recipient),
+            ExecuteMsg::UpdateModelRoyalty {
+                model_id,
+                royalty_info,
+            } => self.update_model_royalty(deps, env, info, model_id, royalty_info),
+            ExecuteMsg::RetireModel { model_id } => self.retire_model(deps, env, info, model_id),
+            ExecuteMsg::DeleteModel { model_id } => self.delete_model(deps, env, info, model_id),
+            ExecuteMsg::TransferCollectionOwnership { new_owner } => {
+                self.transfer_collection_ownership(deps, env, info, new_owner)
+            }
+            ExecuteMsg::AcceptCollectionOwnership {} => {
+                self.accept_collection_ownership(deps, env, info)
+            }
+            ExecuteMsg::UpdateModelUri {
+                model_id,
+                model_uri,
+            } => self.update_model_uri(deps, env, info, model_id, model_uri),
+            ExecuteMsg::PropagateModelUri {
+                model_id,
+                start_after,
+                limit,
+            } => self.propagate_model_uri(deps, env, info, model_id, start_after, limit),
+            ExecuteMsg::UpdateTokenUri {
+                token_id,
+                token_uri,
+            } => self.update_token_uri(deps, env, info, token_id, token_uri),
+            ExecuteMsg::FreezeMetadata {} => self.freeze_metadata(deps, env, info),
         }
     }
 }
@@ -137,84 +219,302 @@ where
 // TODO pull this into some sort of trait extension??
 impl<'a, T, C> NFTContract<'a, T, C>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Serialize + DeserializeOwned + Clone + MetadataExtension,
     C: CustomMsg,
 {
     pub fn mint(
         &self,
         deps: DepsMut,
-        _env: Env,
+        env: Env,
         info: MessageInfo,
         msg: MintMsg<T>,
     ) -> Result<Response<C>, ContractError> {
-        let minter = self.minter.load(deps.storage)?;
+        let token_id = self._mint(deps, &env.block, &info.sender, msg)?;
 
-        if info.sender != minter {
-            return Err(ContractError::Unauthorized {});
+        Ok(Response::new()
+            .set_data(to_binary(&MintResponse {
+                token_id: token_id.clone(),
+            })?)
+            .add_attribute("action", "mint")
+            .add_attribute("minter", info.sender)
+            .add_attribute("token_id", token_id))
+    }
+
+    pub fn batch_mint(
+        &self,
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: BatchMintMsg<T>,
+    ) -> Result<Response<C>, ContractError> {
+        if msg.items.is_empty() || msg.items.len() > MAX_BATCH_SIZE as usize {
+            return Err(ContractError::InvalidBatchSize { max: MAX_BATCH_SIZE });
         }
 
+        let mut token_ids = Vec::with_capacity(msg.items.len());
+        for item in msg.items {
+            let mint_msg = MintMsg {
+                token_id: item.token_id,
+                prefix_model_id: msg.prefix_model_id,
+                owner: item.owner,
+                model_id: msg.model_id.clone(),
+                size: item.size,
+                token_uri: None,
+                royalty_info: None,
+                transferable: None,
+                extension: msg.extension.clone(),
+            };
+            token_ids.push(self._mint(deps.branch(), &env.block, &info.sender, mint_msg)?);
+        }
+
+        Ok(Response::new()
+            .set_data(to_binary(&BatchMintResponse {
+                token_ids: token_ids.clone(),
+            })?)
+            .add_attribute("action", "batch_mint")
+            .add_attribute("minter", info.sender)
+            .add_attribute("model_id", msg.model_id)
+            .add_attribute("count", token_ids.len().to_string())
+            .add_attribute("token_ids", token_ids.join(",")))
+    }
+
+    pub fn batch_transfer(
+        &self,
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        recipient: String,
+        token_ids: Vec<String>,
+    ) -> Result<Response<C>, ContractError> {
+        if token_ids.is_empty() || token_ids.len() > MAX_BATCH_SIZE as usize {
+            return Err(ContractError::InvalidBatchSize { max: MAX_BATCH_SIZE });
+        }
+
+        for token_id in &token_ids {
+            self._transfer_nft(deps.branch(), &env, &info, &recipient, token_id)?;
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "batch_transfer")
+            .add_attribute("sender", info.sender)
+            .add_attribute("recipient", recipient)
+            .add_attribute("count", token_ids.len().to_string())
+            .add_attribute("token_ids", token_ids.join(",")))
+    }
+
+    /// Creates a token after checking the sender may mint it, returns its id
+    pub fn _mint(
+        &self,
+        deps: DepsMut,
+        block: &BlockInfo,
+        sender: &Addr,
+        msg: MintMsg<T>,
+    ) -> Result<String, ContractError> {
         let model = self.models.load(deps.storage, &msg.model_id)?;
+        self.assert_can_mint(deps.storage, sender, &model)?;
+
+        msg.extension.validate()?;
+        if model.retired {
+            return Err(ContractError::ModelRetired {});
+        }
+        self.record_mint(deps.storage, &model, &msg.size)?;
+        let token_uri_override = msg.token_uri.is_some();
+        let token_uri = match msg.token_uri {
+            Some(token_uri) => validate_uri(&token_uri)?,
+            None => model.model_uri,
+        };
+        let token_id = match msg.token_id {
+            Some(token_id) => token_id,
+            None => self.next_token_id(
+                deps.storage,
+                &msg.model_id,
+                msg.prefix_model_id.unwrap_or(false),
+            )?,
+        };
         // create the token
         let token = TokenInfo {
-            token_id: msg.token_id.clone(),
+            token_id: token_id.clone(),
             owner: deps.api.addr_validate(&msg.owner)?,
             approvals: vec![],
             model_id: msg.model_id,
-            token_uri: model.model_uri,
+            token_uri,
+            token_uri_override,
             size: msg.size,
+            royalty_info: parse_override_royalty_info(deps.as_ref(), block, msg.royalty_info)?,
+            transferable: msg.transferable.unwrap_or(model.transferable),
             extension: msg.extension,
         };
 
+        // the id of a token burned on redemption stays taken
+        if REDEMPTIONS.has(deps.storage, &token_id) {
+            return Err(ContractError::Claimed {});
+        }
+        let token_traits = token.extension.traits();
         self.tokens
-            .update(deps.storage, &msg.token_id, |old| match old {
+            .update(deps.storage, &token_id, |old| match old {
                 Some(_) => Err(ContractError::Claimed {}),
                 None => Ok(token),
             })?;
 
+        save_traits(deps.storage, TOKEN_TRAITS, &token_id, &token_traits)?;
         self.increment_tokens(deps.storage)?;
 
-        Ok(Response::new()
-            .add_attribute("action", "mint")
-            .add_attribute("minter", info.sender)
-            .add_attribute("token_id", msg.token_id))
+        Ok(token_id)
     }
 }
 
 impl<'a, T, C> NFTContract<'a, T, C>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Serialize + DeserializeOwned + Clone + MetadataExtension,
     C: CustomMsg,
 {
-    pub fn create_model(
+    pub fn update_minter(
         &self,
         deps: DepsMut,
         _env: Env,
         info: MessageInfo,
-        msg: CreateShoeModelMsg<T>,
+        new_minter: String,
     ) -> Result<Response<C>, ContractError> {
-        let minter = self.minter.load(deps.storage)?;
+        self.assert_minter(deps.storage, &info.sender)?;
 
-        if info.sender != minter {
+        let new_minter_addr = deps.api.addr_validate(&new_minter)?;
+        PENDING_MINTER.save(deps.storage, &new_minter_addr)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "update_minter")
+            .add_attribute("minter", info.sender)
+            .add_attribute("pending_minter", new_minter))
+    }
+
+    pub fn accept_minter(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+    ) -> Result<Response<C>, ContractError> {
+        let pending_minter = PENDING_MINTER
+            .may_load(deps.storage)?
+            .ok_or(ContractError::NoPendingMinter {})?;
+        if info.sender != pending_minter {
             return Err(ContractError::Unauthorized {});
         }
 
-        let model_uri = Url::parse(&msg.model_uri)?;
-        if model_uri.scheme() != "ipfs" {
-            return Err(ContractError::InvalidBaseURI {});
+        self.minter.save(deps.storage, &pending_minter)?;
+        PENDING_MINTER.remove(deps.storage);
+
+        Ok(Response::new()
+            .add_attribute("action", "accept_minter")
+            .add_attribute("minter", info.sender))
+    }
+
+    pub fn renounce_minter(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+    ) -> Result<Response<C>, ContractError> {
+        self.assert_minter(deps.storage, &info.sender)?;
+
+        self.minter.remove(deps.storage);
+        PENDING_MINTER.remove(deps.storage);
+        let minters = MINTERS
+            .keys(deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()?;
+        for minter in minters {
+            MINTERS.remove(deps.storage, &minter);
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "renounce_minter")
+            .add_attribute("minter", info.sender))
+    }
+
+    pub fn add_minter(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        address: String,
+        quota: Option<u64>,
+    ) -> Result<Response<C>, ContractError> {
+        self.assert_minter(deps.storage, &info.sender)?;
+
+        let minter = deps.api.addr_validate(&address)?;
+        // keep the count of an existing minter so a new quota cannot reset it
+        let minted = MINTERS
+            .may_load(deps.storage, &minter)?
+            .map(|minter_quota| minter_quota.minted)
+            .unwrap_or_default();
+        MINTERS.save(deps.storage, &minter, &MinterQuota { quota, minted })?;
+
+        Ok(Response::new()
+            .add_attribute("action", "add_minter")
+            .add_attribute("minter", info.sender)
+            .add_attribute("address", address)
+            .add_attribute("quota", quota.map(|q| q.to_string()).unwrap_or_default()))
+    }
+
+    pub fn remove_minter(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        address: String,
+    ) -> Result<Response<C>, ContractError> {
+        self.assert_minter(deps.storage, &info.sender)?;
+
+        let minter = deps.api.addr_validate(&address)?;
+        MINTERS.remove(deps.storage, &minter);
+
+        Ok(Response::new()
+            .add_attribute("action", "remove_minter")
+            .add_attribute("minter", info.sender)
+            .add_attribute("address", address))
+    }
+}
+
+impl<'a, T, C> NFTContract<'a, T, C>
+where
+    T: Serialize + DeserializeOwned + Clone + MetadataExtension,
+    C: CustomMsg,
+{
+    pub fn create_model(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        msg: CreateShoeModelMsg<T>,
+    ) -> Result<Response<C>, ContractError> {
+        self.assert_minter(deps.storage, &info.sender)?;
+
+        // ids of deleted models that had tokens stay reserved
+        if MODEL_SUPPLY.has(deps.storage, &msg.model_id) {
+            return Err(ContractError::ModelClaimed {});
         }
+        let model_uri = validate_uri(&msg.model_uri)?;
+        msg.extension.validate()?;
+        let size_supply = msg.size_supply.unwrap_or_default();
+        validate_supply(msg.max_supply, &size_supply)?;
 
         // create the shoe model
         let model = ModelInfo {
             model_id: msg.model_id.clone(),
             owner: deps.api.addr_validate(&msg.owner)?,
-            model_uri: model_uri.to_string(),
+            model_uri,
+            royalty_info: parse_override_royalty_info(deps.as_ref(), &env.block, msg.royalty_info)?,
+            max_supply: msg.max_supply,
+            size_supply,
+            retired: false,
+            transferable: msg.transferable.unwrap_or(true),
             extension: msg.extension,
         };
+        let model_traits = model.extension.traits();
         self.models
             .update(deps.storage, &msg.model_id, |old| match old {
                 Some(_) => Err(ContractError::ModelClaimed {}),
                 None => Ok(model),
             })?;
+        save_traits(deps.storage, MODEL_TRAITS, &msg.model_id, &model_traits)?;
 
         self.increment_models(deps.storage)?;
 
@@ -227,24 +527,165 @@ where
 
 impl<'a, T, C> NFTContract<'a, T, C>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Serialize + DeserializeOwned + Clone + MetadataExtension,
     C: CustomMsg,
 {
-    pub fn modify_collection_info(
+    pub fn update_model(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        model_id: String,
+        owner: Option<String>,
+        extension: Option<T>,
+    ) -> Result<Response<C>, ContractError> {
+        self.assert_minter(deps.storage, &info.sender)?;
+
+        let mut model = self.models.load(deps.storage, &model_id)?;
+        let mut response = Response::new()
+            .add_attribute("action", "update_model")
+            .add_attribute("minter", info.sender)
+            .add_attribute("model_id", &model_id);
+
+        if let Some(owner) = owner {
+            let owner_addr = deps.api.addr_validate(&owner)?;
+            set_model_owner(&mut model, owner_addr);
+            response = response.add_attribute("owner", owner);
+        }
+
+        if let Some(extension) = extension {
+            if METADATA_FROZEN.may_load(deps.storage)?.unwrap_or_default() {
+                return Err(ContractError::MetadataFrozen {});
+            }
+            extension.validate()?;
+            remove_traits(deps.storage, MODEL_TRAITS, &model_id, &model.extension.traits());
+            save_traits(deps.storage, MODEL_TRAITS, &model_id, &extension.traits())?;
+            model.extension = extension;
+            response = response.add_attribute("extension", "updated");
+        }
+
+        self.models.save(deps.storage, &model_id, &model)?;
+
+        Ok(response)
+    }
+
+    pub fn transfer_model(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        model_id: String,
+        recipient: String,
+    ) -> Result<Response<C>, ContractError> {
+        let mut model = self.models.load(deps.storage, &model_id)?;
+        if info.sender != model.owner {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        let recipient_addr = deps.api.addr_validate(&recipient)?;
+        set_model_owner(&mut model, recipient_addr);
+        self.models.save(deps.storage, &model_id, &model)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "transfer_model")
+            .add_attribute("sender", info.sender)
+            .add_attribute("recipient", recipient)
+            .add_attribute("model_id", model_id))
+    }
+
+    pub fn update_model_royalty(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        model_id: String,
+        royalty_info: Option<RoyaltyInfoResponse>,
+    ) -> Result<Response<C>, ContractError> {
+        let mut model = self.models.load(deps.storage, &model_id)?;
+        if info.sender != model.owner {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        model.royalty_info = parse_override_royalty_info(deps.as_ref(), &env.block, royalty_info)?;
+        self.models.save(deps.storage, &model_id, &model)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "update_model_royalty")
+            .add_attribute("sender", info.sender)
+            .add_attribute("model_id", model_id)
+            .add_attribute("royalty_info", royalty_info_string(&model.royalty_info)))
+    }
+
+    pub fn retire_model(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        model_id: String,
+    ) -> Result<Response<C>, ContractError> {
+        self.assert_minter(deps.storage, &info.sender)?;
+
+        let mut model = self.models.load(deps.storage, &model_id)?;
+        model.retired = true;
+        self.models.save(deps.storage, &model_id, &model)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "retire_model")
+            .add_attribute("minter", info.sender)
+            .add_attribute("model_id", model_id))
+    }
+
+    pub fn delete_model(
         &self,
         deps: DepsMut,
         _env: Env,
         info: MessageInfo,
+        model_id: String,
+    ) -> Result<Response<C>, ContractError> {
+        self.assert_minter(deps.storage, &info.sender)?;
+
+        let model = self.models.load(deps.storage, &model_id)?;
+        let has_tokens = self
+            .tokens
+            .idx
+            .model
+            .prefix(model_id.clone())
+            .keys_raw(deps.storage, None, None, Order::Ascending)
+            .next()
+            .is_some();
+        if has_tokens {
+            return Err(ContractError::ModelHasTokens {});
+        }
+
+        self.models.remove(deps.storage, &model_id)?;
+        remove_traits(deps.storage, MODEL_TRAITS, &model_id, &model.extension.traits());
+        // supply counters stay behind so the id cannot be re-created with a fresh supply
+        self.decrement_models(deps.storage)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "delete_model")
+            .add_attribute("minter", info.sender)
+            .add_attribute("model_id", model_id))
+    }
+}
+
+impl<'a, T, C> NFTContract<'a, T, C>
+where
+    T: Serialize + DeserializeOwned + Clone + MetadataExtension,
+    C: CustomMsg,
+{
+    pub fn modify_collection_info(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
         description: Option<String>,
         image: Option<String>,
         external_link: Option<String>,
         royalty_info: Option<RoyaltyInfoResponse>,
     ) -> Result<Response<C>, ContractError> {
         let mut collection_info = COLLECTION_INFO.load(deps.storage)?;
-        let minter = self.minter.load(deps.storage)?;
-        if info.sender != minter {
-            return Err(ContractError::Unauthorized {});
-        }
+        assert_collection_owner(deps.storage, &info.sender)?;
 
         if let Some(i) = description.clone() {
             collection_info.description = i;
@@ -258,16 +699,45 @@ where
             collection_info.image = i;
         }
 
-        let modify_royalty_info: Option<RoyaltyInfo> = match royalty_info {
-            Some(royalty_info) => Some(RoyaltyInfo {
-                payment_address: deps.api.addr_validate(&royalty_info.payment_address)?,
-                share: royalty_info.share_validate()?,
-            }),
-            None => None,
-        };
+        let modify_royalty_info = parse_royalty_info(deps.api, royalty_info)?;
 
         collection_info.external_link = external_link.clone();
-        collection_info.royalty_info = modify_royalty_info.clone();
+
+        // lowering the royalty applies at once, raising it is announced ahead of time
+        let current_royalty_info = collection_royalty(deps.storage, &env.block)?;
+        let current_share = total_share(&current_royalty_info);
+        let new_share = total_share(&modify_royalty_info);
+        let effective_at = if new_share > current_share {
+            let timelock = ROYALTY_TIMELOCK.load(deps.storage)?;
+            let window = match ROYALTY_INCREASE_WINDOW.may_load(deps.storage)? {
+                Some(window) if env.block.time < window.start.plus_seconds(timelock.period) => window,
+                _ => RoyaltyIncreaseWindow {
+                    start: env.block.time,
+                    base_share: current_share,
+                },
+            };
+            if new_share > window.base_share + timelock.max_increase {
+                return Err(ContractError::RoyaltyIncreaseTooLarge {
+                    max_increase: timelock.max_increase,
+                });
+            }
+            ROYALTY_INCREASE_WINDOW.save(deps.storage, &window)?;
+
+            let effective_at = env.block.time.plus_seconds(timelock.delay);
+            collection_info.royalty_info = current_royalty_info;
+            PENDING_ROYALTY.save(
+                deps.storage,
+                &PendingRoyalty {
+                    royalty_info: modify_royalty_info.clone(),
+                    effective_at,
+                },
+            )?;
+            effective_at
+        } else {
+            collection_info.royalty_info = modify_royalty_info.clone();
+            PENDING_ROYALTY.remove(deps.storage);
+            env.block.time
+        };
 
         COLLECTION_INFO.save(deps.storage, &collection_info)?;
 
@@ -275,28 +745,393 @@ where
 
         let unwrap_external_link = external_link.unwrap_or(null_string.clone());
         let unwrap_description = description.unwrap_or(null_string.clone());
-        let unwrap_image = image.unwrap_or(null_string.clone());
-        let modify_royalty_info_clone = modify_royalty_info.clone();
-
-        let modify_royalty_info_string = format!(
-            "{} {}",
-            modify_royalty_info_clone.unwrap().payment_address,
-            modify_royalty_info.unwrap().share
-        );
+        let unwrap_image = image.unwrap_or(null_string);
+        let modify_royalty_info_string = royalty_info_string(&modify_royalty_info);
 
         Ok(Response::new()
             .add_attribute("action", "modify_collection_info")
-            .add_attribute("minter", info.sender)
+            .add_attribute("owner", info.sender)
             .add_attribute("new_description", unwrap_description)
             .add_attribute("new_image", unwrap_image)
             .add_attribute("new_external_link", unwrap_external_link)
-            .add_attribute("new_royalty_info", modify_royalty_info_string))
+            .add_attribute("new_royalty_info", modify_royalty_info_string)
+            .add_attribute("royalty_effective_at", effective_at.seconds().to_string()))
+    }
+
+    pub fn transfer_collection_ownership(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        new_owner: String,
+    ) -> Result<Response<C>, ContractError> {
+        assert_collection_owner(deps.storage, &info.sender)?;
+
+        let new_owner_addr = deps.api.addr_validate(&new_owner)?;
+        PENDING_COLLECTION_OWNER.save(deps.storage, &new_owner_addr)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "transfer_collection_ownership")
+            .add_attribute("owner", info.sender)
+            .add_attribute("pending_owner", new_owner))
+    }
+
+    pub fn accept_collection_ownership(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+    ) -> Result<Response<C>, ContractError> {
+        let pending_owner = PENDING_COLLECTION_OWNER
+            .may_load(deps.storage)?
+            .ok_or(ContractError::NoPendingCollectionOwner {})?;
+        if info.sender != pending_owner {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        COLLECTION_OWNER.save(deps.storage, &pending_owner)?;
+        PENDING_COLLECTION_OWNER.remove(deps.storage);
+
+        Ok(Response::new()
+            .add_attribute("action", "accept_collection_ownership")
+            .add_attribute("owner", info.sender))
+    }
+}
+
+impl<'a, T, C> NFTContract<'a, T, C>
+where
+    T: Serialize + DeserializeOwned + Clone + MetadataExtension,
+    C: CustomMsg,
+{
+    pub fn update_model_uri(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        model_id: String,
+        model_uri: String,
+    ) -> Result<Response<C>, ContractError> {
+        self.assert_metadata_editable(deps.as_ref(), &info)?;
+
+        let model_uri = validate_uri(&model_uri)?;
+        let mut model = self.models.load(deps.storage, &model_id)?;
+        model.model_uri = model_uri.clone();
+        self.models.save(deps.storage, &model_id, &model)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "update_model_uri")
+            .add_attribute("sender", info.sender)
+            .add_attribute("model_id", model_id)
+            .add_attribute("model_uri", model_uri))
+    }
+
+    pub fn propagate_model_uri(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        model_id: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> Result<Response<C>, ContractError> {
+        self.assert_metadata_editable(deps.as_ref(), &info)?;
+
+        let model = self.models.load(deps.storage, &model_id)?;
+        let limit = limit.unwrap_or(DEFAULT_PROPAGATE_LIMIT).min(MAX_PROPAGATE_LIMIT) as usize;
+        let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+
+        let tokens = self
+            .tokens
+            .idx
+            .model
+            .prefix(model_id.clone())
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?;
+
+        let last_token_id = if tokens.len() == limit {
+            tokens.last().map(|(token_id, _)| token_id.clone())
+        } else {
+            None
+        };
+
+        let mut updated = 0u32;
+        for (token_id, mut token) in tokens {
+            if token.token_uri_override || token.token_uri == model.model_uri {
+                continue;
+            }
+            token.token_uri = model.model_uri.clone();
+            self.tokens.save(deps.storage, &token_id, &token)?;
+            updated += 1;
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "propagate_model_uri")
+            .add_attribute("sender", info.sender)
+            .add_attribute("model_id", model_id)
+            .add_attribute("updated", updated.to_string())
+            .add_attribute("last_token_id", last_token_id.unwrap_or_default()))
+    }
+
+    pub fn update_token_uri(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        token_id: String,
+        token_uri: Option<String>,
+    ) -> Result<Response<C>, ContractError> {
+        self.assert_metadata_editable(deps.as_ref(), &info)?;
+
+        let mut token = self.tokens.load(deps.storage, &token_id)?;
+        match token_uri {
+            Some(token_uri) => {
+                token.token_uri = validate_uri(&token_uri)?;
+                token.token_uri_override = true;
+            }
+            None => {
+                token.token_uri = self.models.load(deps.storage, &token.model_id)?.model_uri;
+                token.token_uri_override = false;
+            }
+        }
+        self.tokens.save(deps.storage, &token_id, &token)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "update_token_uri")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id)
+            .add_attribute("token_uri", token.token_uri))
+    }
+
+    pub fn freeze_metadata(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+    ) -> Result<Response<C>, ContractError> {
+        assert_collection_owner(deps.storage, &info.sender)?;
+        if METADATA_FROZEN.may_load(deps.storage)?.unwrap_or_default() {
+            return Err(ContractError::MetadataFrozen {});
+        }
+
+        METADATA_FROZEN.save(deps.storage, &true)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "freeze_metadata")
+            .add_attribute("sender", info.sender))
+    }
+
+    fn assert_metadata_editable(&self, deps: Deps, info: &MessageInfo) -> Result<(), ContractError> {
+        assert_collection_owner(deps.storage, &info.sender)?;
+        if METADATA_FROZEN.may_load(deps.storage)?.unwrap_or_default() {
+            return Err(ContractError::MetadataFrozen {});
+        }
+        Ok(())
+    }
+}
+
+impl<'a, T, C> NFTContract<'a, T, C>
+where
+    T: Serialize + DeserializeOwned + Clone + MetadataExtension,
+    C: CustomMsg,
+{
+    pub fn recall_nft(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        token_id: String,
+        recipient: Option<String>,
+    ) -> Result<Response<C>, ContractError> {
+        self.assert_minter(deps.storage, &info.sender)?;
+
+        let mut token = self.tokens.load(deps.storage, &token_id)?;
+        // transferable tokens belong to their owner alone
+        if token.transferable {
+            return Err(ContractError::Unauthorized {});
+        }
+        assert_not_frozen(deps.storage, &token_id)?;
+        assert_not_redeemed(deps.storage, &token_id)?;
+
+        let recipient = match recipient {
+            Some(recipient) => deps.api.addr_validate(&recipient)?,
+            None => info.sender.clone(),
+        };
+        let previous_owner = token.owner;
+        token.owner = recipient.clone();
+        token.approvals = vec![];
+        self.tokens.save(deps.storage, &token_id, &token)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "recall_nft")
+            .add_attribute("minter", info.sender)
+            .add_attribute("previous_owner", previous_owner)
+            .add_attribute("recipient", recipient)
+            .add_attribute("token_id", token_id))
+    }
+}
+
+impl<'a, T, C> NFTContract<'a, T, C>
+where
+    T: Serialize + DeserializeOwned + Clone + MetadataExtension,
+    C: CustomMsg,
+{
+    pub fn freeze_token(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+        reason: String,
+    ) -> Result<Response<C>, ContractError> {
+        assert_collection_owner(deps.storage, &info.sender)?;
+        if reason.is_empty() || reason.len() > MAX_FREEZE_REASON_LENGTH {
+            return Err(ContractError::InvalidFreezeReason {});
+        }
+        // make sure the token exists
+        self.tokens.load(deps.storage, &token_id)?;
+
+        let frozen = FrozenToken {
+            reason: reason.clone(),
+            frozen_at: env.block.time,
+        };
+        FROZEN_TOKENS.save(deps.storage, &token_id, &frozen)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "freeze_token")
+            .add_attribute("owner", info.sender)
+            .add_attribute("token_id", token_id)
+            .add_attribute("reason", reason))
+    }
+
+    pub fn unfreeze_token(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        token_id: String,
+    ) -> Result<Response<C>, ContractError> {
+        assert_collection_owner(deps.storage, &info.sender)?;
+        if !FROZEN_TOKENS.has(deps.storage, &token_id) {
+            return Err(ContractError::TokenNotFrozen {});
+        }
+
+        FROZEN_TOKENS.remove(deps.storage, &token_id);
+
+        Ok(Response::new()
+            .add_attribute("action", "unfreeze_token")
+            .add_attribute("owner", info.sender)
+            .add_attribute("token_id", token_id))
+    }
+
+    pub fn request_redemption(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+    ) -> Result<Response<C>, ContractError> {
+        let token = self.tokens.load(deps.storage, &token_id)?;
+        if token.owner != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+        assert_not_frozen(deps.storage, &token_id)?;
+        assert_not_redeemed(deps.storage, &token_id)?;
+
+        let redemption = Redemption {
+            status: RedemptionStatus::Requested,
+            requested_by: info.sender.clone(),
+            requested_at: env.block.time,
+            redeemed_at: None,
+            burned: false,
+        };
+        REDEMPTIONS.save(deps.storage, &token_id, &redemption)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "request_redemption")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id))
+    }
+
+    pub fn cancel_redemption(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        token_id: String,
+    ) -> Result<Response<C>, ContractError> {
+        let redemption = load_pending_redemption(deps.storage, &token_id)?;
+        if redemption.requested_by != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+        assert_not_frozen(deps.storage, &token_id)?;
+
+        REDEMPTIONS.remove(deps.storage, &token_id);
+
+        Ok(Response::new()
+            .add_attribute("action", "cancel_redemption")
+            .add_attribute("sender", info.sender)
+            .add_attribute("token_id", token_id))
+    }
+
+    pub fn confirm_redemption(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+        burn: bool,
+    ) -> Result<Response<C>, ContractError> {
+        match REDEEMER.may_load(deps.storage)? {
+            Some(redeemer) if redeemer == info.sender => {}
+            _ => return Err(ContractError::Unauthorized {}),
+        }
+        let mut redemption = load_pending_redemption(deps.storage, &token_id)?;
+        assert_not_frozen(deps.storage, &token_id)?;
+
+        if burn {
+            let token = self.tokens.load(deps.storage, &token_id)?;
+            self._burn(deps.storage, &token)?;
+        }
+        redemption.status = RedemptionStatus::Redeemed;
+        redemption.redeemed_at = Some(env.block.time);
+        redemption.burned = burn;
+        REDEMPTIONS.save(deps.storage, &token_id, &redemption)?;
+
+        Ok(Response::new()
+            .add_attribute("action", "confirm_redemption")
+            .add_attribute("redeemer", info.sender)
+            .add_attribute("token_id", token_id)
+            .add_attribute("burned", burn.to_string()))
+    }
+
+    pub fn set_redeemer(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        address: Option<String>,
+    ) -> Result<Response<C>, ContractError> {
+        assert_collection_owner(deps.storage, &info.sender)?;
+
+        match address {
+            Some(ref address) => {
+                let redeemer = deps.api.addr_validate(address)?;
+                REDEEMER.save(deps.storage, &redeemer)?;
+            }
+            None => REDEEMER.remove(deps.storage),
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "set_redeemer")
+            .add_attribute("owner", info.sender)
+            .add_attribute("redeemer", address.unwrap_or_default()))
     }
 }
 
 impl<'a, T, C> Cw721Execute<T, C> for NFTContract<'a, T, C>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Serialize + DeserializeOwned + Clone + MetadataExtension,
     C: CustomMsg,
 {
     type Err = ContractError;
@@ -431,9 +1266,16 @@ where
     ) -> Result<Response<C>, ContractError> {
         let token = self.tokens.load(deps.storage, &token_id)?;
         self.check_can_send(deps.as_ref(), &env, &info, &token)?;
+        assert_not_frozen(deps.storage, &token_id)?;
+        if let Some(Redemption {
+            status: RedemptionStatus::Requested,
+            ..
+        }) = REDEMPTIONS.may_load(deps.storage, &token_id)?
+        {
+            return Err(ContractError::RedemptionPending {});
+        }
 
-        self.tokens.remove(deps.storage, &token_id)?;
-        self.decrement_tokens(deps.storage)?;
+        self._burn(deps.storage, &token)?;
 
         Ok(Response::new()
             .add_attribute("action", "burn")
@@ -445,7 +1287,7 @@ where
 // helpers
 impl<'a, T, C> NFTContract<'a, T, C>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Serialize + DeserializeOwned + Clone + MetadataExtension,
     C: CustomMsg,
 {
     pub fn _transfer_nft(
@@ -457,6 +1299,11 @@ where
         token_id: &str,
     ) -> Result<TokenInfo<T>, ContractError> {
         let mut token = self.tokens.load(deps.storage, token_id)?;
+        if !token.transferable {
+            return Err(ContractError::NotTransferable {});
+        }
+        assert_not_frozen(deps.storage, token_id)?;
+        assert_not_redeemed(deps.storage, token_id)?;
         // ensure we have permissions
         self.check_can_send(deps.as_ref(), env, info, &token)?;
         // set owner and remove existing approvals
@@ -492,6 +1339,12 @@ where
 
         // only difference between approve and revoke
         if add {
+            // nobody but the minter may move a locked token, so approving it is pointless
+            if !token.transferable {
+                return Err(ContractError::NotTransferable {});
+            }
+            assert_not_frozen(deps.storage, token_id)?;
+            assert_not_redeemed(deps.storage, token_id)?;
             // reject expired data as invalid
             let expires = expires.unwrap_or_default();
             if expires.is_expired(&env.block) {
@@ -574,4 +1427,280 @@ where
             None => Err(ContractError::Unauthorized {}),
         }
     }
-}
\ No newline at end of file
+
+    /// Errors unless sender is the main minter
+    pub fn assert_minter(&self, storage: &dyn Storage, sender: &Addr) -> Result<(), ContractError> {
+        match self.minter.may_load(storage)? {
+            Some(minter) if minter == *sender => Ok(()),
+            _ => Err(ContractError::Unauthorized {}),
+        }
+    }
+
+    /// The main minter may always mint and additional minters until their quota is used up.
+    /// A model owner that is not an additional minter mints its own models without a quota,
+    /// one that is counts against it. Nobody mints once minting was renounced
+    fn assert_can_mint(
+        &self,
+        storage: &mut dyn Storage,
+        sender: &Addr,
+        model: &ModelInfo<T>,
+    ) -> Result<(), ContractError> {
+        let minter = self
+            .minter
+            .may_load(storage)?
+            .ok_or(ContractError::Unauthorized {})?;
+        if minter == *sender {
+            return Ok(());
+        }
+
+        let mut minter_quota = match MINTERS.may_load(storage, sender)? {
+            Some(minter_quota) => minter_quota,
+            None if *sender == model.owner => return Ok(()),
+            None => return Err(ContractError::Unauthorized {}),
+        };
+        if minter_quota.quota.is_some_and(|quota| minter_quota.minted >= quota) {
+            return Err(ContractError::MinterQuotaExceeded {});
+        }
+        minter_quota.minted += 1;
+        MINTERS.save(storage, sender, &minter_quota)?;
+        Ok(())
+    }
+
+    /// Hands out the next numeric id, skipping ids that were already taken explicitly
+    fn next_token_id(
+        &self,
+        storage: &mut dyn Storage,
+        model_id: &str,
+        prefix_model_id: bool,
+    ) -> StdResult<String> {
+        let mut last = LAST_TOKEN_ID.may_load(storage)?.unwrap_or_default();
+        let token_id = loop {
+            last += 1;
+            let token_id = if prefix_model_id {
+                format!("{}-{}", model_id, last)
+            } else {
+                last.to_string()
+            };
+            // ids of burned redeemed tokens stay claimed
+            if self.tokens.may_load(storage, &token_id)?.is_none() && !REDEMPTIONS.has(storage, &token_id) {
+                break token_id;
+            }
+        };
+        LAST_TOKEN_ID.save(storage, &last)?;
+        Ok(token_id)
+    }
+
+    /// Removes a token along with its trait entries and counts it as burned
+    pub fn _burn(&self, storage: &mut dyn Storage, token: &TokenInfo<T>) -> StdResult<()> {
+        self.tokens.remove(storage, &token.token_id)?;
+        remove_traits(storage, TOKEN_TRAITS, &token.token_id, &token.extension.traits());
+        self.record_burn(storage, token)?;
+        self.decrement_tokens(storage)?;
+        Ok(())
+    }
+
+    fn record_mint(
+        &self,
+        storage: &mut dyn Storage,
+        model: &ModelInfo<T>,
+        size: &str,
+    ) -> Result<(), ContractError> {
+        let mut supply = MODEL_SUPPLY.may_load(storage, &model.model_id)?.unwrap_or_default();
+        if model.max_supply.is_some_and(|max| supply.minted >= max) {
+            return Err(ContractError::SoldOut {});
+        }
+
+        let key = (model.model_id.as_str(), size);
+        let mut size_counter = SIZE_SUPPLY.may_load(storage, key)?.unwrap_or_default();
+        if !model.size_supply.is_empty() {
+            let size_cap = model
+                .size_supply
+                .iter()
+                .find(|s| s.size == size)
+                .ok_or_else(|| ContractError::SizeNotAvailable { size: size.to_string() })?;
+            if size_counter.minted >= size_cap.quantity {
+                return Err(ContractError::SizeSoldOut { size: size.to_string() });
+            }
+        }
+
+        supply.minted += 1;
+        size_counter.minted += 1;
+        MODEL_SUPPLY.save(storage, &model.model_id, &supply)?;
+        SIZE_SUPPLY.save(storage, key, &size_counter)?;
+        Ok(())
+    }
+
+    fn record_burn(&self, storage: &mut dyn Storage, token: &TokenInfo<T>) -> StdResult<()> {
+        let mut supply = MODEL_SUPPLY.may_load(storage, &token.model_id)?.unwrap_or_default();
+        supply.burned += 1;
+        MODEL_SUPPLY.save(storage, &token.model_id, &supply)?;
+
+        let key = (token.model_id.as_str(), token.size.as_str());
+        let mut size_counter = SIZE_SUPPLY.may_load(storage, key)?.unwrap_or_default();
+        size_counter.burned += 1;
+        SIZE_SUPPLY.save(storage, key, &size_counter)
+    }
+}
+
+fn parse_royalty_info(
+    api: &dyn Api,
+    royalty_info: Option<RoyaltyInfoResponse>,
+) -> Result<Option<RoyaltyInfo>, ContractError> {
+    match royalty_info {
+        Some(royalty_info) => {
+            royalty_info.share_validate()?;
+            let recipients = royalty_info
+                .recipients
+                .into_iter()
+                .map(|recipient| {
+                    Ok(RoyaltyRecipient {
+                        payment_address: api.addr_validate(&recipient.payment_address)?,
+                        share: recipient.share,
+                    })
+                })
+                .collect::<Result<Vec<_>, ContractError>>()?;
+            Ok(Some(RoyaltyInfo { recipients }))
+        }
+        None => Ok(None),
+    }
+}
+
+fn assert_collection_owner(storage: &dyn Storage, sender: &Addr) -> Result<(), ContractError> {
+    if *sender != COLLECTION_OWNER.load(storage)? {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
+
+/// Model and token royalties skip the time-lock, so they may not exceed the collection
+/// royalty currently in force
+fn parse_override_royalty_info(
+    deps: Deps,
+    block: &BlockInfo,
+    royalty_info: Option<RoyaltyInfoResponse>,
+) -> Result<Option<RoyaltyInfo>, ContractError> {
+    let royalty_info = parse_royalty_info(deps.api, royalty_info)?;
+    let collection_share = total_share(&collection_royalty(deps.storage, block)?);
+    if total_share(&royalty_info) > collection_share {
+        return Err(ContractError::RoyaltyExceedsCollection {});
+    }
+    Ok(royalty_info)
+}
+
+fn validate_royalty_timelock(timelock: &RoyaltyTimelock) -> Result<(), ContractError> {
+    if timelock.period == 0
+        || timelock.max_increase.is_zero()
+        || timelock.max_increase > Decimal::percent(20)
+    {
+        return Err(ContractError::InvalidRoyaltyTimelock {});
+    }
+    Ok(())
+}
+
+fn total_share(royalty_info: &Option<RoyaltyInfo>) -> Decimal {
+    royalty_info
+        .as_ref()
+        .map(|royalty_info| royalty_info.total_share())
+        .unwrap_or_default()
+}
+
+fn assert_not_frozen(storage: &dyn Storage, token_id: &str) -> Result<(), ContractError> {
+    match FROZEN_TOKENS.may_load(storage, token_id)? {
+        Some(frozen) => Err(ContractError::TokenFrozen {
+            reason: frozen.reason,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Tokens are locked while a redemption is pending and for good once redeemed
+fn assert_not_redeemed(storage: &dyn Storage, token_id: &str) -> Result<(), ContractError> {
+    match REDEMPTIONS.may_load(storage, token_id)? {
+        Some(redemption) => match redemption.status {
+            RedemptionStatus::Requested => Err(ContractError::RedemptionPending {}),
+            RedemptionStatus::Redeemed => Err(ContractError::AlreadyRedeemed {}),
+        },
+        None => Ok(()),
+    }
+}
+
+fn load_pending_redemption(storage: &dyn Storage, token_id: &str) -> Result<Redemption, ContractError> {
+    match REDEMPTIONS.may_load(storage, token_id)? {
+        Some(redemption) if redemption.status == RedemptionStatus::Requested => Ok(redemption),
+        _ => Err(ContractError::NoPendingRedemption {}),
+    }
+}
+
+fn royalty_info_string(royalty_info: &Option<RoyaltyInfo>) -> String {
+    royalty_info
+        .as_ref()
+        .map(|royalty_info| {
+            royalty_info
+                .recipients
+                .iter()
+                .map(|recipient| format!("{} {}", recipient.payment_address, recipient.share))
+                .collect::<Vec<_>>()
+                .join(",")
+        })
+        .unwrap_or_default()
+}
+
+/// Changes the owner of a model, moving the royalty shares paid to the old owner along with it
+fn set_model_owner<T>(model: &mut ModelInfo<T>, owner: Addr) {
+    if let Some(ref mut royalty_info) = model.royalty_info {
+        for recipient in royalty_info.recipients.iter_mut() {
+            if recipient.payment_address == model.owner {
+                recipient.payment_address = owner.clone();
+            }
+        }
+    }
+    model.owner = owner;
+}
+
+fn save_traits(
+    storage: &mut dyn Storage,
+    index: Map<(&str, &str, &str), Empty>,
+    id: &str,
+    traits: &[Trait],
+) -> StdResult<()> {
+    for t in traits {
+        index.save(storage, (&t.trait_type, &t.value, id), &Empty {})?;
+    }
+    Ok(())
+}
+
+fn remove_traits(
+    storage: &mut dyn Storage,
+    index: Map<(&str, &str, &str), Empty>,
+    id: &str,
+    traits: &[Trait],
+) {
+    for t in traits {
+        index.remove(storage, (&t.trait_type, &t.value, id));
+    }
+}
+
+fn validate_supply(max_supply: Option<u64>, size_supply: &[SizeSupply]) -> Result<(), ContractError> {
+    if max_supply == Some(0) {
+        return Err(ContractError::InvalidSupply {});
+    }
+    let mut total: u64 = 0;
+    for (i, size) in size_supply.iter().enumerate() {
+        if size.quantity == 0 || size_supply[..i].iter().any(|other| other.size == size.size) {
+            return Err(ContractError::InvalidSupply {});
+        }
+        total = total.checked_add(size.quantity).ok_or(ContractError::InvalidSupply {})?;
+    }
+    if max_supply.is_some_and(|max| total > max) {
+        return Err(ContractError::InvalidSupply {});
+    }
+    Ok(())
+}
+
+fn validate_uri(uri: &str) -> Result<String, ContractError> {
+    let url = Url::parse(uri)?;
+    if url.scheme() != "ipfs" {
+        return Err(ContractError::InvalidBaseURI {});
+    }
+    Ok(url.to_string())
+}
@@ -0,0 +1,1647 @@
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage};
+use cosmwasm_std::{
+    from_binary, from_slice, to_binary, Decimal, DepsMut, Empty, Env, OwnedDeps, Response, Uint128,
+};
+use cw721::Cw721QueryMsg;
+use serde::de::DeserializeOwned;
+
+use crate::msg::{
+    BatchMintItem, BatchMintMsg, BatchMintResponse, CheckRoyaltiesResponse, CollectionInfoResponse,
+    CollectionOwnerResponse, CreateShoeModelMsg, Cw2981QueryMsg, EffectiveRoyaltyResponse,
+    ExecuteMsg, FrozenStatusResponse, InstantiateMsg, MinterQuotaResponse, MinterResponse, MintMsg,
+    MintResponse, ModelInfoResponse, ModelsResponse, ModelSupplyResponse, NumModelsResponse,
+    NumTokensResponse, QueryMsg, RedemptionResponse, RoyaltiesInfoResponse, RoyaltyInfoResponse,
+    RoyaltyPaymentsResponse, RoyaltyRecipientResponse, RoyaltyScheduleResponse, ShoeExtension,
+    SizeSupplyResponse, TokensResponse, TokenStatusResponse,
+};
+use crate::state::{CollectionInfo, RedemptionStatus, RoyaltyTimelock, SizeSupply};
+use crate::{ContractError, Extension, Metadata, NFTContract, Trait};
+
+const MINTER: &str = "minter";
+const CREATOR: &str = "creator";
+const DESIGNER: &str = "designer";
+const OWNER: &str = "owner";
+const REDEEMER: &str = "redeemer";
+const MODEL_ID: &str = "air";
+const TOKEN_ID: &str = "1";
+const WEEK: u64 = 7 * 24 * 60 * 60;
+const DAY: u64 = 24 * 60 * 60;
+
+type Contract<'a> = NFTContract<'a, Extension, Empty>;
+
+fn royalty(percent: u64) -> Option<RoyaltyInfoResponse> {
+    Some(RoyaltyInfoResponse {
+        recipients: vec![RoyaltyRecipientResponse {
+            payment_address: DESIGNER.to_string(),
+            share: Decimal::percent(percent),
+        }],
+    })
+}
+
+fn instantiate_msg(royalty_timelock: Option<RoyaltyTimelock>) -> InstantiateMsg {
+    InstantiateMsg {
+        name: "Sneakers".to_string(),
+        symbol: "SNKR".to_string(),
+        minter: MINTER.to_string(),
+        collection_info: CollectionInfo {
+            creator: CREATOR.to_string(),
+            description: "Limited sneakers".to_string(),
+            image: "ipfs://collection".to_string(),
+            external_link: None,
+            royalty_info: royalty(5),
+        },
+        royalty_timelock,
+    }
+}
+
+/// Collection with a 5% royalty and the default time-lock, one model and one token of `OWNER`
+fn setup(contract: &Contract) -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+    let mut deps = mock_dependencies();
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), instantiate_msg(None))
+        .unwrap();
+    create_model(contract, deps.as_mut(), None).unwrap();
+    mint(contract, deps.as_mut(), TOKEN_ID, None).unwrap();
+    deps
+}
+
+/// Model `model_id` owned by `DESIGNER` without caps
+fn model_msg(model_id: &str) -> CreateShoeModelMsg<Extension> {
+    CreateShoeModelMsg {
+        model_id: model_id.to_string(),
+        owner: DESIGNER.to_string(),
+        model_uri: format!("ipfs://{}", model_id),
+        royalty_info: None,
+        max_supply: None,
+        size_supply: None,
+        transferable: None,
+        extension: None,
+    }
+}
+
+fn create_model(
+    contract: &Contract,
+    deps: DepsMut,
+    royalty_info: Option<RoyaltyInfoResponse>,
+) -> Result<Response, ContractError> {
+    let msg = CreateShoeModelMsg {
+        royalty_info,
+        ..model_msg(MODEL_ID)
+    };
+    create_model_as(contract, deps, MINTER, msg)
+}
+
+fn create_model_as(
+    contract: &Contract,
+    deps: DepsMut,
+    sender: &str,
+    msg: CreateShoeModelMsg<Extension>,
+) -> Result<Response, ContractError> {
+    contract.execute(deps, mock_env(), mock_info(sender, &[]), ExecuteMsg::CreateShoeModel(msg))
+}
+
+/// Mint of a size 42 `MODEL_ID` pair for `OWNER`
+fn mint_msg(token_id: &str) -> MintMsg<Extension> {
+    MintMsg {
+        token_id: Some(token_id.to_string()),
+        prefix_model_id: None,
+        owner: OWNER.to_string(),
+        model_id: MODEL_ID.to_string(),
+        size: "42".to_string(),
+        token_uri: None,
+        royalty_info: None,
+        transferable: None,
+        extension: None,
+    }
+}
+
+fn mint(
+    contract: &Contract,
+    deps: DepsMut,
+    token_id: &str,
+    royalty_info: Option<RoyaltyInfoResponse>,
+) -> Result<Response, ContractError> {
+    let msg = MintMsg {
+        royalty_info,
+        ..mint_msg(token_id)
+    };
+    mint_as(contract, deps, MINTER, msg)
+}
+
+fn mint_as(
+    contract: &Contract,
+    deps: DepsMut,
+    sender: &str,
+    msg: MintMsg<Extension>,
+) -> Result<Response, ContractError> {
+    contract.execute(deps, mock_env(), mock_info(sender, &[]), ExecuteMsg::Mint(msg))
+}
+
+fn modify_royalty(
+    contract: &Contract,
+    deps: DepsMut,
+    env: Env,
+    royalty_info: Option<RoyaltyInfoResponse>,
+) -> Result<Response, ContractError> {
+    let msg = ExecuteMsg::ModifyCollectionInfo {
+        description: None,
+        image: None,
+        external_link: None,
+        royalty_info,
+    };
+    contract.execute(deps, env, mock_info(CREATOR, &[]), msg)
+}
+
+fn royalty_schedule(
+    contract: &Contract,
+    deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    env: Env,
+) -> RoyaltyScheduleResponse {
+    from_binary(&contract.query(deps.as_ref(), env, QueryMsg::RoyaltySchedule {}).unwrap()).unwrap()
+}
+
+fn env_after(seconds: u64) -> Env {
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(seconds);
+    env
+}
+
+#[test]
+fn royalty_increase_waits_for_delay() {
+    let contract = Contract::default();
+    let mut deps = setup(&contract);
+
+    modify_royalty(&contract, deps.as_mut(), mock_env(), royalty(7)).unwrap();
+    let schedule = royalty_schedule(&contract, &deps, mock_env());
+    assert_eq!(schedule.current, royalty(5));
+    assert_eq!(schedule.pending, royalty(7));
+    assert_eq!(schedule.effective_at, Some(mock_env().block.time.plus_seconds(WEEK)));
+
+    let schedule = royalty_schedule(&contract, &deps, env_after(WEEK));
+    assert_eq!(schedule.current, royalty(7));
+    assert_eq!(schedule.pending, None);
+}
+
+#[test]
+fn royalty_increase_is_capped_per_period() {
+    let contract = Contract::default();
+    let mut deps = setup(&contract);
+    let too_large = ContractError::RoyaltyIncreaseTooLarge {
+        max_increase: Decimal::percent(2),
+    };
+
+    let err = modify_royalty(&contract, deps.as_mut(), mock_env(), royalty(8)).unwrap_err();
+    assert_eq!(err, too_large);
+
+    // increases within one period are measured from the royalty at its start
+    modify_royalty(&contract, deps.as_mut(), mock_env(), royalty(6)).unwrap();
+    modify_royalty(&contract, deps.as_mut(), env_after(DAY), royalty(7)).unwrap();
+    let err = modify_royalty(&contract, deps.as_mut(), env_after(2 * DAY), royalty(8)).unwrap_err();
+    assert_eq!(err, too_large);
+
+    // the next period starts from the 7% in force by then
+    modify_royalty(&contract, deps.as_mut(), env_after(WEEK + DAY), royalty(9)).unwrap();
+    let schedule = royalty_schedule(&contract, &deps, env_after(WEEK + DAY));
+    assert_eq!(schedule.current, royalty(7));
+    assert_eq!(schedule.pending, royalty(9));
+}
+
+#[test]
+fn royalty_decrease_applies_at_once() {
+    let contract = Contract::default();
+    let mut deps = setup(&contract);
+
+    modify_royalty(&contract, deps.as_mut(), mock_env(), royalty(7)).unwrap();
+    modify_royalty(&contract, deps.as_mut(), mock_env(), royalty(3)).unwrap();
+
+    let schedule = royalty_schedule(&contract, &deps, mock_env());
+    assert_eq!(schedule.current, royalty(3));
+    assert_eq!(schedule.pending, None);
+    assert_eq!(schedule.effective_at, None);
+}
+
+#[test]
+fn royalty_overrides_are_capped_at_collection_royalty() {
+    let contract = Contract::default();
+    let mut deps = mock_dependencies();
+    contract
+        .instantiate(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), instantiate_msg(None))
+        .unwrap();
+
+    let err = create_model(&contract, deps.as_mut(), royalty(6)).unwrap_err();
+    assert_eq!(err, ContractError::RoyaltyExceedsCollection {});
+    create_model(&contract, deps.as_mut(), royalty(5)).unwrap();
+
+    // an announced increase does not count until it is in force
+    modify_royalty(&contract, deps.as_mut(), mock_env(), royalty(7)).unwrap();
+    let msg: ExecuteMsg<Extension> = ExecuteMsg::UpdateModelRoyalty {
+        model_id: MODEL_ID.to_string(),
+        royalty_info: royalty(6),
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), mock_info(DESIGNER, &[]), msg.clone())
+        .unwrap_err();
+    assert_eq!(err, ContractError::RoyaltyExceedsCollection {});
+    contract
+        .execute(deps.as_mut(), env_after(WEEK), mock_info(DESIGNER, &[]), msg)
+        .unwrap();
+
+    let err = mint(&contract, deps.as_mut(), TOKEN_ID, royalty(6)).unwrap_err();
+    assert_eq!(err, ContractError::RoyaltyExceedsCollection {});
+    mint(&contract, deps.as_mut(), TOKEN_ID, royalty(4)).unwrap();
+}
+
+#[test]
+fn invalid_royalty_timelock_is_rejected() {
+    let contract = Contract::default();
+    let timelocks = [
+        RoyaltyTimelock { period: 0, ..RoyaltyTimelock::default() },
+        RoyaltyTimelock { max_increase: Decimal::zero(), ..RoyaltyTimelock::default() },
+        RoyaltyTimelock { max_increase: Decimal::percent(21), ..RoyaltyTimelock::default() },
+    ];
+    for timelock in timelocks {
+        let mut deps = mock_dependencies();
+        let msg = instantiate_msg(Some(timelock));
+        let err = contract
+            .instantiate(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg)
+            .unwrap_err();
+        assert_eq!(err, ContractError::InvalidRoyaltyTimelock {});
+    }
+}
+
+fn query<R: DeserializeOwned>(
+    contract: &Contract,
+    deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    msg: QueryMsg,
+) -> R {
+    from_binary(&contract.query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap()
+}
+
+fn recipient(address: &str, percent: u64) -> RoyaltyRecipientResponse {
+    RoyaltyRecipientResponse {
+        payment_address: address.to_string(),
+        share: Decimal::percent(percent),
+    }
+}
+
+fn effective_royalty(
+    contract: &Contract,
+    deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    token_id: &str,
+) -> Option<RoyaltyInfoResponse> {
+    let msg = QueryMsg::EffectiveRoyalty {
+        token_id: token_id.to_string(),
+    };
+    query::<EffectiveRoyaltyResponse>(contract, deps, msg).royalty_info
+}
+
+fn update_model_royalty(
+    contract: &Contract,
+    deps: DepsMut,
+    sender: &str,
+    royalty_info: Option<RoyaltyInfoResponse>,
+) -> Result<Response, ContractError> {
+    let msg = ExecuteMsg::UpdateModelRoyalty {
+        model_id: MODEL_ID.to_string(),
+        royalty_info,
+    };
+    contract.execute(deps, mock_env(), mock_info(sender, &[]), msg)
+}
+
+#[test]
+fn token_royalty_overrides_model_and_collection() {
+    let contract = Contract::default();
+    let mut deps = setup(&contract);
+    assert_eq!(effective_royalty(&contract, &deps, TOKEN_ID), royalty(5));
+
+    let model_royalty = Some(RoyaltyInfoResponse {
+        recipients: vec![recipient(CREATOR, 3)],
+    });
+    let err =
+        update_model_royalty(&contract, deps.as_mut(), OWNER, model_royalty.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    update_model_royalty(&contract, deps.as_mut(), DESIGNER, model_royalty.clone()).unwrap();
+    assert_eq!(effective_royalty(&contract, &deps, TOKEN_ID), model_royalty);
+
+    let token_royalty = Some(RoyaltyInfoResponse {
+        recipients: vec![recipient(OWNER, 2)],
+    });
+    mint(&contract, deps.as_mut(), "2", token_royalty.clone()).unwrap();
+    assert_eq!(effective_royalty(&contract, &deps, "2"), token_royalty);
+
+    update_model_royalty(&contract, deps.as_mut(), DESIGNER, None).unwrap();
+    assert_eq!(effective_royalty(&contract, &deps, TOKEN_ID), royalty(5));
+    assert_eq!(effective_royalty(&contract, &deps, "2"), token_royalty);
+}
+
+#[test]
+fn cw2981_queries_report_the_effective_royalty() {
+    let contract = Contract::default();
+    let mut deps = setup(&contract);
+    let split = RoyaltyInfoResponse {
+        recipients: vec![recipient(CREATOR, 3), recipient(DESIGNER, 1)],
+    };
+    mint(&contract, deps.as_mut(), "2", Some(split)).unwrap();
+
+    let royalty_info = |token_id: &str| QueryMsg::Extension {
+        msg: Cw2981QueryMsg::RoyaltyInfo {
+            token_id: token_id.to_string(),
+            sale_price: Uint128::new(1000),
+        },
+    };
+    let res: RoyaltiesInfoResponse = query(&contract, &deps, royalty_info(TOKEN_ID));
+    assert_eq!(res.address, DESIGNER);
+    assert_eq!(res.royalty_amount, Uint128::new(50));
+    // the single CW2981 receiver is reported the whole split royalty
+    let res: RoyaltiesInfoResponse = query(&contract, &deps, royalty_info("2"));
+    assert_eq!(res.address, CREATOR);
+    assert_eq!(res.royalty_amount, Uint128::new(40));
+
+    let msg = QueryMsg::Extension {
+        msg: Cw2981QueryMsg::CheckRoyalties {},
+    };
+    let res: CheckRoyaltiesResponse = query(&contract, &deps, msg);
+    assert!(res.royalty_payments);
+
+    // the CW2981 message shape used by other marketplaces
+    let msg: QueryMsg = from_slice(
+        br#"{"extension":{"msg":{"royalty_info":{"token_id":"1","sale_price":"1000"}}}}"#,
+    )
+    .unwrap();
+    assert_eq!(msg, royalty_info(TOKEN_ID));
+}
+
+#[test]
+fn royalty_is_split_across_recipients() {
+    let contract = Contract::default();
+    let mut deps = setup(&contract);
+    let split = RoyaltyInfoResponse {
+        recipients: vec![recipient(DESIGNER, 3), recipient(CREATOR, 2)],
+    };
+    mint(&contract, deps.as_mut(), "2", Some(split)).unwrap();
+
+    let msg = QueryMsg::RoyaltyPayments {
+        token_id: "2".to_string(),
+        sale_price: Uint128::new(1000),
+    };
+    let res: RoyaltyPaymentsResponse = query(&contract, &deps, msg);
+    assert_eq!(
+        res.payments,
+        vec![
+            RoyaltiesInfoResponse {
+                address: DESIGNER.to_string(),
+                royalty_amount: Uint128::new(30),
+            },
+            RoyaltiesInfoResponse {
+                address: CREATOR.to_string(),
+                royalty_amount: Uint128::new(20),
+            },
+        ]
+    );
+
+    let invalid = [
+        vec![],
+        vec![recipient(DESIGNER, 3), recipient(CREATOR, 0)],
+        vec![
+            RoyaltyRecipientResponse {
+                payment_address: DESIGNER.to_string(),
+                share: Decimal::permille(1),
+            };
+            11
+        ],
+    ];
+    for recipients in invalid {
+        let royalty_info = Some(RoyaltyInfoResponse { recipients });
+        let err = mint(&contract, deps.as_mut(), "3", royalty_info).unwrap_err();
+        assert_eq!(err, ContractError::InvalidRoyalities {});
+    }
+}
+
+/// Sends a standard cw721 query, as wallets and explorers do
+fn cw721_query<R: DeserializeOwned>(
+    contract: &Contract,
+    deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    msg: Cw721QueryMsg,
+) -> R {
+    let msg: QueryMsg = from_binary(&to_binary(&msg).unwrap()).unwrap();
+    query(contract, deps, msg)
+}
+
+#[test]
+fn cw721_queries_get_cw721_responses() {
+    let contract = Contract::default();
+    let deps = setup(&contract);
+
+    let msg = Cw721QueryMsg::NftInfo {
+        token_id: TOKEN_ID.to_string(),
+    };
+    let info: cw721::NftInfoResponse<ShoeExtension<Extension>> = cw721_query(&contract, &deps, msg);
+    assert_eq!(info.token_uri, Some("ipfs://air".to_string()));
+    assert_eq!(info.extension.model_id, MODEL_ID);
+    assert_eq!(info.extension.size, "42");
+
+    let msg = Cw721QueryMsg::AllNftInfo {
+        token_id: TOKEN_ID.to_string(),
+        include_expired: None,
+    };
+    let all_info: cw721::AllNftInfoResponse<ShoeExtension<Extension>> =
+        cw721_query(&contract, &deps, msg);
+    assert_eq!(all_info.access.owner, OWNER);
+    assert_eq!(all_info.info, info);
+
+    let msg = Cw721QueryMsg::ContractInfo {};
+    let res: cw721::ContractInfoResponse = cw721_query(&contract, &deps, msg);
+    assert_eq!(res.symbol, "SNKR");
+    let res: cw721::NumTokensResponse = cw721_query(&contract, &deps, Cw721QueryMsg::NumTokens {});
+    assert_eq!(res.count, 1);
+    let msg = Cw721QueryMsg::Tokens {
+        owner: OWNER.to_string(),
+        start_after: None,
+        limit: None,
+    };
+    let res: cw721::TokensResponse = cw721_query(&contract, &deps, msg);
+    assert_eq!(res.tokens, vec![TOKEN_ID]);
+}
+
+fn metadata(attributes: &[(&str, &str)]) -> Extension {
+    Some(Metadata {
+        name: Some("Air".to_string()),
+        description: None,
+        image: Some("ipfs://air/image.png".to_string()),
+        colorway: Some("red".to_string()),
+        release_date: Some("2024-02-29".to_string()),
+        attributes: Some(
+            attributes
+                .iter()
+                .map(|(trait_type, value)| Trait {
+                    trait_type: trait_type.to_string(),
+                    value: value.to_string(),
+                })
+                .collect(),
+        ),
+    })
+}
+
+fn by_trait(trait_type: &str, value: &str, models: bool) -> QueryMsg {
+    let (trait_type, value) = (trait_type.to_string(), value.to_string());
+    if models {
+        QueryMsg::ModelsByTrait {
+            trait_type,
+            value,
+            start_after: None,
+            limit: None,
+        }
+    } else {
+        QueryMsg::TokensByTrait {
+            trait_type,
+            value,
+            start_after: None,
+            limit: None,
+        }
+    }
+}
+
+#[test]
+fn invalid_metadata_is_rejected() {
+    let contract = Contract::default();
+    let mut deps = setup(&contract);
+    let invalid_metadata = |reason: &str| ContractError::InvalidMetadata {
+        reason: reason.to_string(),
+    };
+
+    let mut extension = metadata(&[]);
+    extension.as_mut().unwrap().release_date = Some("2023-02-29".to_string());
+    let cases = vec![
+        (extension, invalid_metadata("release_date must be YYYY-MM-DD")),
+        (
+            metadata(&[("color", "red"), ("color", "blue")]),
+            invalid_metadata("duplicate trait_type"),
+        ),
+        (metadata(&[("color", "")]), invalid_metadata("invalid attribute")),
+    ];
+    for (extension, expected) in cases {
+        let msg = MintMsg {
+            extension,
+            ..mint_msg("2")
+        };
+        assert_eq!(mint_as(&contract, deps.as_mut(), MINTER, msg).unwrap_err(), expected);
+    }
+
+    let mut extension = metadata(&[]);
+    extension.as_mut().unwrap().description = Some("a".repeat(513));
+    let msg = MintMsg {
+        extension,
+        ..mint_msg("2")
+    };
+    let err = mint_as(&contract, deps.as_mut(), MINTER, msg).unwrap_err();
+    assert_eq!(err, ContractError::DescriptionTooLong {});
+
+    let mut extension = metadata(&[]);
+    extension.as_mut().unwrap().image = Some("not a url".to_string());
+    let msg = ExecuteMsg::UpdateModel {
+        model_id: MODEL_ID.to_string(),
+        owner: None,
+        extension: Some(extension),
+    };
+    let err = contract.execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), msg).unwrap_err();
+    assert!(matches!(err, ContractError::Parse(_)));
+}
+
+#[test]
+fn tokens_and_models_are_indexed_by_trait() {
+    let contract = Contract::default();
+    let mut deps = setup(&contract);
+    let msg = MintMsg {
+        extension: metadata(&[("color", "red")]),
+        ..mint_msg("2")
+    };
+    mint_as(&contract, deps.as_mut(), MINTER, msg).unwrap();
+    let msg = MintMsg {
+        extension: metadata(&[("color", "blue"), ("material", "suede")]),
+        ..mint_msg("3")
+    };
+    mint_as(&contract, deps.as_mut(), MINTER, msg).unwrap();
+
+    let res: TokensResponse = query(&contract, &deps, by_trait("color", "red", false));
+    assert_eq!(res.tokens, vec!["2"]);
+    let res: TokensResponse = query(&contract, &deps, by_trait("material", "suede", false));
+    assert_eq!(res.tokens, vec!["3"]);
+
+    let msg: ExecuteMsg<Extension> = ExecuteMsg::Burn { token_id: "2".to_string() };
+    contract.execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+    let res: TokensResponse = query(&contract, &deps, by_trait("color", "red", false));
+    assert!(res.tokens.is_empty());
+
+    let update_model = |extension| ExecuteMsg::UpdateModel {
+        model_id: MODEL_ID.to_string(),
+        owner: None,
+        extension: Some(extension),
+    };
+    let msg = update_model(metadata(&[("line", "retro")]));
+    contract.execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), msg).unwrap();
+    let res: ModelsResponse = query(&contract, &deps, by_trait("line", "retro", true));
+    assert_eq!(res.models, vec![MODEL_ID]);
+
+    let msg = update_model(metadata(&[("line", "runner")]));
+    contract.execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), msg).unwrap();
+    let res: ModelsResponse = query(&contract, &deps, by_trait("line", "retro", true));
+    assert!(res.models.is_empty());
+    let res: ModelsResponse = query(&contract, &deps, by_trait("line", "runner", true));
+    assert_eq!(res.models, vec![MODEL_ID]);
+}
+
+fn token_uri(
+    contract: &Contract,
+    deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    token_id: &str,
+) -> String {
+    contract.tokens.load(&deps.storage, token_id).unwrap().token_uri
+}
+
+fn attribute(res: &Response, key: &str) -> String {
+    res.attributes.iter().find(|attr| attr.key == key).unwrap().value.clone()
+}
+
+#[test]
+fn model_uri_change_propagates_in_batches() {
+    let contract = Contract::default();
+    let mut deps = setup(&contract);
+    mint(&contract, deps.as_mut(), "2", None).unwrap();
+    mint(&contract, deps.as_mut(), "3", None).unwrap();
+    let msg = MintMsg {
+        token_uri: Some("https://own".to_string()),
+        ..mint_msg("4")
+    };
+    let err = mint_as(&contract, deps.as_mut(), MINTER, msg).unwrap_err();
+    assert_eq!(err, ContractError::InvalidBaseURI {});
+    let msg = MintMsg {
+        token_uri: Some("ipfs://own".to_string()),
+        ..mint_msg("4")
+    };
+    mint_as(&contract, deps.as_mut(), MINTER, msg).unwrap();
+
+    let update_uri = |model_uri: &str| ExecuteMsg::UpdateModelUri {
+        model_id: MODEL_ID.to_string(),
+        model_uri: model_uri.to_string(),
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), update_uri("ipfs://air-v2"))
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), update_uri("https://air-v2"))
+        .unwrap_err();
+    assert_eq!(err, ContractError::InvalidBaseURI {});
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), update_uri("ipfs://air-v2"))
+        .unwrap();
+    assert_eq!(token_uri(&contract, &deps, TOKEN_ID), "ipfs://air");
+
+    let propagate = |start_after: Option<String>| ExecuteMsg::PropagateModelUri {
+        model_id: MODEL_ID.to_string(),
+        start_after,
+        limit: Some(2),
+    };
+    let res = contract
+        .execute(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), propagate(None))
+        .unwrap();
+    assert_eq!(attribute(&res, "updated"), "2");
+    assert_eq!(attribute(&res, "last_token_id"), "2");
+    assert_eq!(token_uri(&contract, &deps, "2"), "ipfs://air-v2");
+    assert_eq!(token_uri(&contract, &deps, "3"), "ipfs://air");
+
+    // tokens with their own uri keep it
+    let msg = propagate(Some("2".to_string()));
+    let res = contract
+        .execute(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg)
+        .unwrap();
+    assert_eq!(attribute(&res, "updated"), "1");
+    assert_eq!(token_uri(&contract, &deps, "3"), "ipfs://air-v2");
+    assert_eq!(token_uri(&contract, &deps, "4"), "ipfs://own");
+    let msg = propagate(Some("4".to_string()));
+    let res = contract
+        .execute(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg)
+        .unwrap();
+    assert_eq!(attribute(&res, "updated"), "0");
+    assert_eq!(attribute(&res, "last_token_id"), "");
+
+    let msg = ExecuteMsg::UpdateTokenUri {
+        token_id: "4".to_string(),
+        token_uri: None,
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg)
+        .unwrap();
+    assert_eq!(token_uri(&contract, &deps, "4"), "ipfs://air-v2");
+}
+
+#[test]
+fn frozen_metadata_cannot_change() {
+    let contract = Contract::default();
+    let mut deps = setup(&contract);
+
+    let msg: ExecuteMsg<Extension> = ExecuteMsg::FreezeMetadata {};
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), msg.clone())
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg.clone())
+        .unwrap();
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::MetadataFrozen {});
+
+    let msgs = vec![
+        ExecuteMsg::UpdateTokenUri {
+            token_id: TOKEN_ID.to_string(),
+            token_uri: Some("ipfs://own".to_string()),
+        },
+        ExecuteMsg::UpdateModelUri {
+            model_id: MODEL_ID.to_string(),
+            model_uri: "ipfs://air-v2".to_string(),
+        },
+        ExecuteMsg::PropagateModelUri {
+            model_id: MODEL_ID.to_string(),
+            start_after: None,
+            limit: None,
+        },
+    ];
+    for msg in msgs {
+        let err = contract
+            .execute(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg)
+            .unwrap_err();
+        assert_eq!(err, ContractError::MetadataFrozen {});
+    }
+    let msg = ExecuteMsg::UpdateModel {
+        model_id: MODEL_ID.to_string(),
+        owner: None,
+        extension: Some(metadata(&[])),
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::MetadataFrozen {});
+}
+
+fn sizes(sizes: &[(&str, u64)]) -> Option<Vec<SizeSupply>> {
+    Some(
+        sizes
+            .iter()
+            .map(|(size, quantity)| SizeSupply {
+                size: size.to_string(),
+                quantity: *quantity,
+            })
+            .collect(),
+    )
+}
+
+/// Mint of a `model_id` pair of `size` for `OWNER`
+fn mint_size(model_id: &str, token_id: &str, size: &str) -> MintMsg<Extension> {
+    MintMsg {
+        model_id: model_id.to_string(),
+        size: size.to_string(),
+        ..mint_msg(token_id)
+    }
+}
+
+#[test]
+fn invalid_supply_is_rejected() {
+    let contract = Contract::default();
+    let mut deps = setup(&contract);
+    let cases = [
+        (None, sizes(&[("42", 0)])),
+        (None, sizes(&[("42", 1), ("42", 1)])),
+        (Some(2), sizes(&[("42", 2), ("43", 1)])),
+        (Some(0), None),
+    ];
+    for (max_supply, size_supply) in cases {
+        let msg = CreateShoeModelMsg {
+            max_supply,
+            size_supply,
+            ..model_msg("jordan")
+        };
+        let err = create_model_as(&contract, deps.as_mut(), MINTER, msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidSupply {});
+    }
+}
+
+#[test]
+fn mints_stop_at_the_supply_caps() {
+    let contract = Contract::default();
+    let mut deps = setup(&contract);
+    let msg = CreateShoeModelMsg {
+        max_supply: Some(2),
+        ..model_msg("limited")
+    };
+    create_model_as(&contract, deps.as_mut(), MINTER, msg).unwrap();
+    mint_as(&contract, deps.as_mut(), MINTER, mint_size("limited", "l1", "42")).unwrap();
+    mint_as(&contract, deps.as_mut(), MINTER, mint_size("limited", "l2", "43")).unwrap();
+    let msg = mint_size("limited", "l3", "44");
+    let err = mint_as(&contract, deps.as_mut(), MINTER, msg).unwrap_err();
+    assert_eq!(err, ContractError::SoldOut {});
+
+    let msg = CreateShoeModelMsg {
+        size_supply: sizes(&[("42", 2), ("43", 1)]),
+        ..model_msg("sized")
+    };
+    create_model_as(&contract, deps.as_mut(), MINTER, msg).unwrap();
+    mint_as(&contract, deps.as_mut(), MINTER, mint_size("sized", "s1", "42")).unwrap();
+    mint_as(&contract, deps.as_mut(), MINTER, mint_size("sized", "s2", "42")).unwrap();
+    mint_as(&contract, deps.as_mut(), MINTER, mint_size("sized", "s3", "43")).unwrap();
+    let msg = mint_size("sized", "s4", "42");
+    let err = mint_as(&contract, deps.as_mut(), MINTER, msg).unwrap_err();
+    assert_eq!(err, ContractError::SizeSoldOut { size: "42".to_string() });
+    let msg = mint_size("sized", "s4", "44");
+    let err = mint_as(&contract, deps.as_mut(), MINTER, msg).unwrap_err();
+    assert_eq!(err, ContractError::SizeNotAvailable { size: "44".to_string() });
+
+    // burned pairs do not free up supply
+    let msg: ExecuteMsg<Extension> = ExecuteMsg::Burn { token_id: "s1".to_string() };
+    contract.execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+    let msg = mint_size("sized", "s4", "42");
+    let err = mint_as(&contract, deps.as_mut(), MINTER, msg).unwrap_err();
+    assert_eq!(err, ContractError::SizeSoldOut { size: "42".to_string() });
+
+    let msg = QueryMsg::ModelSupply {
+        model_id: "sized".to_string(),
+    };
+    let supply: ModelSupplyResponse = query(&contract, &deps, msg);
+    assert_eq!((supply.minted, supply.burned, supply.remaining), (3, 1, None));
+    assert_eq!(
+        supply.sizes,
+        vec![
+            SizeSupplyResponse {
+                size: "42".to_string(),
+                max_supply: Some(2),
+                minted: 2,
+                burned: 1,
+                remaining: Some(0),
+            },
+            SizeSupplyResponse {
+                size: "43".to_string(),
+                max_supply: Some(1),
+                minted: 1,
+                burned: 0,
+                remaining: Some(0),
+            },
+        ]
+    );
+}
+
+fn tokens_by_model(
+    contract: &Contract,
+    deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    size: Option<&str>,
+    start_after: Option<&str>,
+) -> Vec<String> {
+    let model_id = MODEL_ID.to_string();
+    let (start_after, limit) = (start_after.map(String::from), Some(2));
+    let msg = match size {
+        Some(size) => QueryMsg::TokensByModelAndSize {
+            model_id,
+            size: size.to_string(),
+            start_after,
+            limit,
+        },
+        None => QueryMsg::TokensByModel {
+            model_id,
+            start_after,
+            limit,
+        },
+    };
+    query::<TokensResponse>(contract, deps, msg).tokens
+}
+
+fn num_tokens_by_model(
+    contract: &Contract,
+    deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    size: Option<&str>,
+) -> u64 {
+    let msg = QueryMsg::NumTokensByModel {
+        model_id: MODEL_ID.to_string(),
+        size: size.map(String::from),
+    };
+    query::<NumTokensResponse>(contract, deps, msg).count
+}
+
+#[test]
+fn tokens_are_listed_by_model_and_size() {
+    let contract = Contract::default();
+    let mut deps = setup(&contract);
+    mint_as(&contract, deps.as_mut(), MINTER, mint_size(MODEL_ID, "2", "43")).unwrap();
+    mint(&contract, deps.as_mut(), "3", None).unwrap();
+    mint(&contract, deps.as_mut(), "4", None).unwrap();
+    create_model_as(&contract, deps.as_mut(), MINTER, model_msg("jordan")).unwrap();
+    mint_as(&contract, deps.as_mut(), MINTER, mint_size("jordan", "5", "42")).unwrap();
+
+    assert_eq!(tokens_by_model(&contract, &deps, None, None), vec!["1", "2"]);
+    assert_eq!(tokens_by_model(&contract, &deps, None, Some("2")), vec!["3", "4"]);
+    assert!(tokens_by_model(&contract, &deps, None, Some("4")).is_empty());
+    assert_eq!(tokens_by_model(&contract, &deps, Some("42"), None), vec!["1", "3"]);
+    assert_eq!(tokens_by_model(&contract, &deps, Some("42"), Some("3")), vec!["4"]);
+    assert_eq!(num_tokens_by_model(&contract, &deps, None), 4);
+    assert_eq!(num_tokens_by_model(&contract, &deps, Some("42")), 3);
+
+    let msg: ExecuteMsg<Extension> = ExecuteMsg::Burn { token_id: "3".to_string() };
+    contract.execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+    assert_eq!(tokens_by_model(&contract, &deps, Some("42"), None), vec!["1", "4"]);
+    assert_eq!(num_tokens_by_model(&contract, &deps, None), 3);
+    assert_eq!(num_tokens_by_model(&contract, &deps, Some("42")), 2);
+    assert_eq!(num_tokens_by_model(&contract, &deps, Some("43")), 1);
+}
+
+fn num_models(contract: &Contract, deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>) -> u64 {
+    query::<NumModelsResponse>(contract, deps, QueryMsg::NumModels {}).count
+}
+
+#[test]
+fn retired_model_keeps_its_tokens() {
+    let contract = Contract::default();
+    let mut deps = setup(&contract);
+
+    let msg: ExecuteMsg<Extension> = ExecuteMsg::UpdateModel {
+        model_id: MODEL_ID.to_string(),
+        owner: Some(CREATOR.to_string()),
+        extension: None,
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), mock_info(DESIGNER, &[]), msg.clone())
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), msg)
+        .unwrap();
+    let msg = QueryMsg::ModelInfo {
+        model_id: MODEL_ID.to_string(),
+    };
+    let model: ModelInfoResponse<Extension> = query(&contract, &deps, msg);
+    assert_eq!(model.owner, CREATOR);
+
+    let msg: ExecuteMsg<Extension> = ExecuteMsg::RetireModel {
+        model_id: MODEL_ID.to_string(),
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg.clone())
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), msg)
+        .unwrap();
+    let err = mint(&contract, deps.as_mut(), "2", None).unwrap_err();
+    assert_eq!(err, ContractError::ModelRetired {});
+    transfer(&contract, deps.as_mut()).unwrap();
+}
+
+#[test]
+fn model_with_tokens_cannot_be_deleted() {
+    let contract = Contract::default();
+    let mut deps = setup(&contract);
+    create_model_as(&contract, deps.as_mut(), MINTER, model_msg("jordan")).unwrap();
+    assert_eq!(num_models(&contract, &deps), 2);
+
+    let delete = |model_id: &str| ExecuteMsg::DeleteModel {
+        model_id: model_id.to_string(),
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), delete(MODEL_ID))
+        .unwrap_err();
+    assert_eq!(err, ContractError::ModelHasTokens {});
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), delete("jordan"))
+        .unwrap();
+    assert_eq!(num_models(&contract, &deps), 1);
+    // a model that never had tokens can be created again
+    create_model_as(&contract, deps.as_mut(), MINTER, model_msg("jordan")).unwrap();
+
+    let msg: ExecuteMsg<Extension> = ExecuteMsg::Burn {
+        token_id: TOKEN_ID.to_string(),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg)
+        .unwrap();
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), delete(MODEL_ID))
+        .unwrap();
+    assert_eq!(num_models(&contract, &deps), 1);
+    let err = create_model(&contract, deps.as_mut(), None).unwrap_err();
+    assert_eq!(err, ContractError::ModelClaimed {});
+}
+
+fn models_by_owner(
+    contract: &Contract,
+    deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    owner: &str,
+) -> Vec<String> {
+    let msg = QueryMsg::ModelsByOwner {
+        owner: owner.to_string(),
+        start_after: None,
+        limit: None,
+    };
+    query::<ModelsResponse>(contract, deps, msg).models
+}
+
+#[test]
+fn model_owner_mints_own_models() {
+    let contract = Contract::default();
+    let mut deps = setup(&contract);
+    let msg = CreateShoeModelMsg {
+        owner: CREATOR.to_string(),
+        ..model_msg("jordan")
+    };
+    create_model_as(&contract, deps.as_mut(), MINTER, msg).unwrap();
+
+    mint_as(&contract, deps.as_mut(), DESIGNER, mint_msg("2")).unwrap();
+    let msg = mint_size("jordan", "3", "42");
+    let err = mint_as(&contract, deps.as_mut(), DESIGNER, msg).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let err = mint_as(&contract, deps.as_mut(), OWNER, mint_msg("3")).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    // only the minter creates models
+    let err = create_model_as(&contract, deps.as_mut(), DESIGNER, model_msg("yeezy")).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+}
+
+#[test]
+fn transferred_model_moves_rights_and_royalty() {
+    let contract = Contract::default();
+    let mut deps = setup(&contract);
+    update_model_royalty(&contract, deps.as_mut(), DESIGNER, royalty(3)).unwrap();
+    assert_eq!(models_by_owner(&contract, &deps, DESIGNER), vec![MODEL_ID]);
+
+    let msg: ExecuteMsg<Extension> = ExecuteMsg::TransferModel {
+        model_id: MODEL_ID.to_string(),
+        recipient: CREATOR.to_string(),
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), msg.clone())
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(DESIGNER, &[]), msg)
+        .unwrap();
+
+    assert!(models_by_owner(&contract, &deps, DESIGNER).is_empty());
+    assert_eq!(models_by_owner(&contract, &deps, CREATOR), vec![MODEL_ID]);
+    let model_royalty = Some(RoyaltyInfoResponse {
+        recipients: vec![recipient(CREATOR, 3)],
+    });
+    assert_eq!(effective_royalty(&contract, &deps, TOKEN_ID), model_royalty);
+
+    let err = mint_as(&contract, deps.as_mut(), DESIGNER, mint_msg("2")).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    mint_as(&contract, deps.as_mut(), CREATOR, mint_msg("2")).unwrap();
+    let err = update_model_royalty(&contract, deps.as_mut(), DESIGNER, None).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+}
+
+fn batch_mint(
+    contract: &Contract,
+    deps: DepsMut,
+    token_ids: &[&str],
+) -> Result<Response, ContractError> {
+    let items = token_ids
+        .iter()
+        .map(|token_id| BatchMintItem {
+            token_id: Some(token_id.to_string()),
+            owner: OWNER.to_string(),
+            size: "42".to_string(),
+        })
+        .collect();
+    let msg = ExecuteMsg::BatchMint(BatchMintMsg {
+        model_id: MODEL_ID.to_string(),
+        items,
+        prefix_model_id: None,
+        extension: None,
+    });
+    contract.execute(deps, mock_env(), mock_info(MINTER, &[]), msg)
+}
+
+fn batch_transfer(
+    contract: &Contract,
+    deps: DepsMut,
+    sender: &str,
+    token_ids: &[&str],
+) -> Result<Response, ContractError> {
+    let msg = ExecuteMsg::BatchTransfer {
+        recipient: DESIGNER.to_string(),
+        token_ids: token_ids.iter().map(|token_id| token_id.to_string()).collect(),
+    };
+    contract.execute(deps, mock_env(), mock_info(sender, &[]), msg)
+}
+
+#[test]
+fn tokens_are_minted_and_transferred_in_batches() {
+    let contract = Contract::default();
+    let mut deps = setup(&contract);
+    let invalid_size = ContractError::InvalidBatchSize { max: 100 };
+    let too_many: Vec<String> = (2..103).map(|n| n.to_string()).collect();
+    let too_many: Vec<&str> = too_many.iter().map(String::as_str).collect();
+
+    assert_eq!(batch_mint(&contract, deps.as_mut(), &[]).unwrap_err(), invalid_size);
+    assert_eq!(batch_mint(&contract, deps.as_mut(), &too_many).unwrap_err(), invalid_size);
+    let res = batch_mint(&contract, deps.as_mut(), &["2", "3"]).unwrap();
+    let data: BatchMintResponse = from_binary(res.data.as_ref().unwrap()).unwrap();
+    assert_eq!(data.token_ids, vec!["2", "3"]);
+    assert_eq!(attribute(&res, "count"), "2");
+    assert_eq!(attribute(&res, "token_ids"), "2,3");
+    let err = batch_mint(&contract, deps.as_mut(), &["4", TOKEN_ID]).unwrap_err();
+    assert_eq!(err, ContractError::Claimed {});
+
+    assert_eq!(batch_transfer(&contract, deps.as_mut(), OWNER, &[]).unwrap_err(), invalid_size);
+    let err = batch_transfer(&contract, deps.as_mut(), OWNER, &too_many).unwrap_err();
+    assert_eq!(err, invalid_size);
+    let err = batch_transfer(&contract, deps.as_mut(), MINTER, &["2", "3"]).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let res = batch_transfer(&contract, deps.as_mut(), OWNER, &[TOKEN_ID, "2", "3"]).unwrap();
+    assert_eq!(attribute(&res, "count"), "3");
+    assert_eq!(attribute(&res, "token_ids"), "1,2,3");
+    for token_id in [TOKEN_ID, "2", "3"] {
+        let token = contract.tokens.load(&deps.storage, token_id).unwrap();
+        assert_eq!(token.owner, DESIGNER);
+    }
+}
+
+fn mint_assigned(contract: &Contract, deps: DepsMut, prefix_model_id: bool) -> String {
+    let msg = MintMsg {
+        token_id: None,
+        prefix_model_id: Some(prefix_model_id),
+        ..mint_msg(TOKEN_ID)
+    };
+    let res = mint_as(contract, deps, MINTER, msg).unwrap();
+    let data: MintResponse = from_binary(res.data.as_ref().unwrap()).unwrap();
+    assert_eq!(attribute(&res, "token_id"), data.token_id);
+    data.token_id
+}
+
+#[test]
+fn token_ids_are_assigned_when_omitted() {
+    let contract = Contract::default();
+    let mut deps = setup(&contract);
+
+    // "1" was taken explicitly by `setup`
+    assert_eq!(mint_assigned(&contract, deps.as_mut(), false), "2");
+    assert_eq!(mint_assigned(&contract, deps.as_mut(), true), "air-3");
+    mint(&contract, deps.as_mut(), "4", None).unwrap();
+    assert_eq!(mint_assigned(&contract, deps.as_mut(), false), "5");
+
+    let item = BatchMintItem {
+        token_id: None,
+        owner: OWNER.to_string(),
+        size: "42".to_string(),
+    };
+    let msg = ExecuteMsg::BatchMint(BatchMintMsg {
+        model_id: MODEL_ID.to_string(),
+        items: vec![item.clone(), item],
+        prefix_model_id: Some(true),
+        extension: None,
+    });
+    let res = contract
+        .execute(deps.as_mut(), mock_env(), mock_info(MINTER, &[]), msg)
+        .unwrap();
+    let data: BatchMintResponse = from_binary(&res.data.unwrap()).unwrap();
+    assert_eq!(data.token_ids, vec!["air-6", "air-7"]);
+    let msg = QueryMsg::NumTokens {};
+    assert_eq!(query::<NumTokensResponse>(&contract, &deps, msg).count, 7);
+}
+
+fn execute_as(
+    contract: &Contract,
+    deps: DepsMut,
+    sender: &str,
+    msg: ExecuteMsg<Extension>,
+) -> Result<Response, ContractError> {
+    contract.execute(deps, mock_env(), mock_info(sender, &[]), msg)
+}
+
+fn minter(
+    contract: &Contract,
+    deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+) -> MinterResponse {
+    query(contract, deps, QueryMsg::Minter {})
+}
+
+#[test]
+fn minter_role_is_handed_over_in_two_steps() {
+    let contract = Contract::default();
+    let mut deps = setup(&contract);
+    let update = ExecuteMsg::UpdateMinter {
+        new_minter: DESIGNER.to_string(),
+    };
+
+    let err = execute_as(&contract, deps.as_mut(), DESIGNER, ExecuteMsg::AcceptMinter {});
+    assert_eq!(err.unwrap_err(), ContractError::NoPendingMinter {});
+    let err = execute_as(&contract, deps.as_mut(), DESIGNER, update.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    execute_as(&contract, deps.as_mut(), MINTER, update).unwrap();
+    assert_eq!(minter(&contract, &deps).pending_minter, Some(DESIGNER.to_string()));
+
+    let err = execute_as(&contract, deps.as_mut(), OWNER, ExecuteMsg::AcceptMinter {});
+    assert_eq!(err.unwrap_err(), ContractError::Unauthorized {});
+    // the old minter keeps the role until the handover is accepted
+    mint(&contract, deps.as_mut(), "2", None).unwrap();
+    execute_as(&contract, deps.as_mut(), DESIGNER, ExecuteMsg::AcceptMinter {}).unwrap();
+
+    let res = minter(&contract, &deps);
+    assert_eq!(res.minter, Some(DESIGNER.to_string()));
+    assert_eq!(res.pending_minter, None);
+    let err = mint(&contract, deps.as_mut(), "3", None).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let msg = CreateShoeModelMsg {
+        owner: CREATOR.to_string(),
+        ..model_msg("jordan")
+    };
+    create_model_as(&contract, deps.as_mut(), DESIGNER, msg).unwrap();
+}
+
+#[test]
+fn additional_minters_mint_within_quota() {
+    let contract = Contract::default();
+    let mut deps = setup(&contract);
+    let add = ExecuteMsg::AddMinter {
+        address: OWNER.to_string(),
+        quota: Some(2),
+    };
+
+    let err = execute_as(&contract, deps.as_mut(), OWNER, add.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    execute_as(&contract, deps.as_mut(), MINTER, add).unwrap();
+    mint_as(&contract, deps.as_mut(), OWNER, mint_msg("2")).unwrap();
+    mint_as(&contract, deps.as_mut(), OWNER, mint_msg("3")).unwrap();
+    let err = mint_as(&contract, deps.as_mut(), OWNER, mint_msg("4")).unwrap_err();
+    assert_eq!(err, ContractError::MinterQuotaExceeded {});
+    let minters = vec![MinterQuotaResponse {
+        address: OWNER.to_string(),
+        quota: Some(2),
+        minted: 2,
+    }];
+    assert_eq!(minter(&contract, &deps).minters, minters);
+
+    let remove = ExecuteMsg::RemoveMinter {
+        address: OWNER.to_string(),
+    };
+    execute_as(&contract, deps.as_mut(), MINTER, remove).unwrap();
+    assert!(minter(&contract, &deps).minters.is_empty());
+    let err = mint_as(&contract, deps.as_mut(), OWNER, mint_msg("4")).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+}
+
+#[test]
+fn renounced_minting_stops_all_mints() {
+    let contract = Contract::default();
+    let mut deps = setup(&contract);
+    let add = ExecuteMsg::AddMinter {
+        address: OWNER.to_string(),
+        quota: None,
+    };
+    execute_as(&contract, deps.as_mut(), MINTER, add).unwrap();
+
+    let err = execute_as(&contract, deps.as_mut(), OWNER, ExecuteMsg::RenounceMinter {});
+    assert_eq!(err.unwrap_err(), ContractError::Unauthorized {});
+    execute_as(&contract, deps.as_mut(), MINTER, ExecuteMsg::RenounceMinter {}).unwrap();
+
+    let res = minter(&contract, &deps);
+    assert_eq!(res.minter, None);
+    assert!(res.minters.is_empty());
+    for sender in [MINTER, OWNER, DESIGNER] {
+        let err = mint_as(&contract, deps.as_mut(), sender, mint_msg("2")).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+    }
+}
+
+fn collection_owner(
+    contract: &Contract,
+    deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+) -> CollectionOwnerResponse {
+    query(contract, deps, QueryMsg::CollectionOwner {})
+}
+
+#[test]
+fn collection_settings_need_the_collection_owner() {
+    let contract = Contract::default();
+    let mut deps = setup(&contract);
+    let describe = ExecuteMsg::ModifyCollectionInfo {
+        description: Some("Sneakers".to_string()),
+        image: None,
+        external_link: None,
+        royalty_info: None,
+    };
+    let update_uri = ExecuteMsg::UpdateModelUri {
+        model_id: MODEL_ID.to_string(),
+        model_uri: "ipfs://air2".to_string(),
+    };
+    let freeze_token = ExecuteMsg::FreezeToken {
+        token_id: TOKEN_ID.to_string(),
+        reason: "counterfeit".to_string(),
+    };
+    let set_redeemer = ExecuteMsg::SetRedeemer { address: None };
+
+    // the minter does not manage the collection
+    for msg in [describe.clone(), update_uri, freeze_token, set_redeemer] {
+        let err = execute_as(&contract, deps.as_mut(), MINTER, msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+    }
+    let err = execute_as(&contract, deps.as_mut(), MINTER, ExecuteMsg::FreezeMetadata {});
+    assert_eq!(err.unwrap_err(), ContractError::Unauthorized {});
+    execute_as(&contract, deps.as_mut(), CREATOR, describe).unwrap();
+    let res: CollectionInfoResponse = query(&contract, &deps, QueryMsg::CollectionInfo {});
+    assert_eq!(res.description, "Sneakers");
+}
+
+#[test]
+fn collection_ownership_is_handed_over_in_two_steps() {
+    let contract = Contract::default();
+    let mut deps = setup(&contract);
+    let transfer_ownership = ExecuteMsg::TransferCollectionOwnership {
+        new_owner: DESIGNER.to_string(),
+    };
+    let accept = ExecuteMsg::AcceptCollectionOwnership {};
+    let owner = CollectionOwnerResponse {
+        owner: CREATOR.to_string(),
+        pending_owner: None,
+    };
+    assert_eq!(collection_owner(&contract, &deps), owner);
+
+    let err = execute_as(&contract, deps.as_mut(), DESIGNER, accept.clone()).unwrap_err();
+    assert_eq!(err, ContractError::NoPendingCollectionOwner {});
+    let err = execute_as(&contract, deps.as_mut(), MINTER, transfer_ownership.clone());
+    assert_eq!(err.unwrap_err(), ContractError::Unauthorized {});
+    execute_as(&contract, deps.as_mut(), CREATOR, transfer_ownership).unwrap();
+    let pending = CollectionOwnerResponse {
+        pending_owner: Some(DESIGNER.to_string()),
+        ..owner
+    };
+    assert_eq!(collection_owner(&contract, &deps), pending);
+
+    let err = execute_as(&contract, deps.as_mut(), OWNER, accept.clone()).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    execute_as(&contract, deps.as_mut(), DESIGNER, accept).unwrap();
+    let owner = CollectionOwnerResponse {
+        owner: DESIGNER.to_string(),
+        pending_owner: None,
+    };
+    assert_eq!(collection_owner(&contract, &deps), owner);
+
+    let err = execute_as(&contract, deps.as_mut(), CREATOR, ExecuteMsg::FreezeMetadata {});
+    assert_eq!(err.unwrap_err(), ContractError::Unauthorized {});
+    execute_as(&contract, deps.as_mut(), DESIGNER, ExecuteMsg::FreezeMetadata {}).unwrap();
+    // creating models and minting stay with the minter
+    mint(&contract, deps.as_mut(), "2", None).unwrap();
+}
+
+fn recall(
+    contract: &Contract,
+    deps: DepsMut,
+    sender: &str,
+    token_id: &str,
+    recipient: Option<&str>,
+) -> Result<Response, ContractError> {
+    let msg = ExecuteMsg::RecallNft {
+        token_id: token_id.to_string(),
+        recipient: recipient.map(str::to_string),
+    };
+    execute_as(contract, deps, sender, msg)
+}
+
+fn token_owner(
+    contract: &Contract,
+    deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    token_id: &str,
+) -> String {
+    contract.tokens.load(&deps.storage, token_id).unwrap().owner.to_string()
+}
+
+#[test]
+fn soulbound_tokens_only_move_by_recall() {
+    let contract = Contract::default();
+    let mut deps = setup(&contract);
+    let msg = CreateShoeModelMsg {
+        transferable: Some(false),
+        ..model_msg("badge")
+    };
+    create_model_as(&contract, deps.as_mut(), MINTER, msg).unwrap();
+    mint_as(&contract, deps.as_mut(), MINTER, mint_size("badge", "2", "42")).unwrap();
+
+    let moves = [
+        ExecuteMsg::TransferNft {
+            recipient: DESIGNER.to_string(),
+            token_id: "2".to_string(),
+        },
+        ExecuteMsg::SendNft {
+            contract: DESIGNER.to_string(),
+            token_id: "2".to_string(),
+            msg: to_binary("sell").unwrap(),
+        },
+        ExecuteMsg::Approve {
+            spender: DESIGNER.to_string(),
+            token_id: "2".to_string(),
+            expires: None,
+        },
+    ];
+    for msg in moves {
+        let err = execute_as(&contract, deps.as_mut(), OWNER, msg).unwrap_err();
+        assert_eq!(err, ContractError::NotTransferable {});
+    }
+
+    let err = recall(&contract, deps.as_mut(), OWNER, "2", None).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let err = recall(&contract, deps.as_mut(), MINTER, TOKEN_ID, None).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    recall(&contract, deps.as_mut(), MINTER, "2", None).unwrap();
+    assert_eq!(token_owner(&contract, &deps, "2"), MINTER);
+    recall(&contract, deps.as_mut(), MINTER, "2", Some(DESIGNER)).unwrap();
+    assert_eq!(token_owner(&contract, &deps, "2"), DESIGNER);
+
+    // soulbound tokens can still be burned by their owner
+    let burn = ExecuteMsg::Burn {
+        token_id: "2".to_string(),
+    };
+    execute_as(&contract, deps.as_mut(), DESIGNER, burn).unwrap();
+    assert!(contract.tokens.may_load(&deps.storage, "2").unwrap().is_none());
+}
+
+#[test]
+fn token_transferable_flag_overrides_the_model() {
+    let contract = Contract::default();
+    let mut deps = setup(&contract);
+    let msg = CreateShoeModelMsg {
+        transferable: Some(false),
+        ..model_msg("badge")
+    };
+    create_model_as(&contract, deps.as_mut(), MINTER, msg).unwrap();
+    let msg = MintMsg {
+        transferable: Some(true),
+        ..mint_size("badge", "2", "42")
+    };
+    mint_as(&contract, deps.as_mut(), MINTER, msg).unwrap();
+    let msg = MintMsg {
+        transferable: Some(false),
+        ..mint_msg("3")
+    };
+    mint_as(&contract, deps.as_mut(), MINTER, msg).unwrap();
+
+    let transfer_to_designer = |token_id: &str| ExecuteMsg::TransferNft {
+        recipient: DESIGNER.to_string(),
+        token_id: token_id.to_string(),
+    };
+    execute_as(&contract, deps.as_mut(), OWNER, transfer_to_designer("2")).unwrap();
+    assert_eq!(token_owner(&contract, &deps, "2"), DESIGNER);
+    let err = execute_as(&contract, deps.as_mut(), OWNER, transfer_to_designer("3"));
+    assert_eq!(err.unwrap_err(), ContractError::NotTransferable {});
+    let err = recall(&contract, deps.as_mut(), MINTER, "2", None).unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+}
+
+fn transfer(contract: &Contract, deps: DepsMut) -> Result<Response, ContractError> {
+    let msg = ExecuteMsg::TransferNft {
+        recipient: DESIGNER.to_string(),
+        token_id: TOKEN_ID.to_string(),
+    };
+    contract.execute(deps, mock_env(), mock_info(OWNER, &[]), msg)
+}
+
+fn confirm_redemption(contract: &Contract, deps: DepsMut, burn: bool) -> Result<Response, ContractError> {
+    let msg = ExecuteMsg::ConfirmRedemption {
+        token_id: TOKEN_ID.to_string(),
+        burn,
+    };
+    contract.execute(deps, mock_env(), mock_info(REDEEMER, &[]), msg)
+}
+
+/// Same as `setup` with `REDEEMER` set and a redemption of the token requested by `OWNER`
+fn setup_redemption(contract: &Contract) -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+    let mut deps = setup(contract);
+    let msg = ExecuteMsg::SetRedeemer {
+        address: Some(REDEEMER.to_string()),
+    };
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(CREATOR, &[]), msg)
+        .unwrap();
+
+    let msg: ExecuteMsg<Extension> = ExecuteMsg::RequestRedemption {
+        token_id: TOKEN_ID.to_string(),
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), mock_info(DESIGNER, &[]), msg.clone())
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg)
+        .unwrap();
+    deps
+}
+
+#[test]
+fn redemption_locks_token_until_confirmed() {
+    let contract = Contract::default();
+    let mut deps = setup_redemption(&contract);
+
+    let err = transfer(&contract, deps.as_mut()).unwrap_err();
+    assert_eq!(err, ContractError::RedemptionPending {});
+    let query = QueryMsg::TokenStatus {
+        token_id: TOKEN_ID.to_string(),
+    };
+    let status: TokenStatusResponse =
+        from_binary(&contract.query(deps.as_ref(), mock_env(), query).unwrap()).unwrap();
+    assert_eq!(status.redemption, Some(RedemptionStatus::Requested));
+
+    let msg = ExecuteMsg::ConfirmRedemption {
+        token_id: TOKEN_ID.to_string(),
+        burn: true,
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    confirm_redemption(&contract, deps.as_mut(), true).unwrap();
+
+    let query = QueryMsg::Redemption {
+        token_id: TOKEN_ID.to_string(),
+    };
+    let redemption: RedemptionResponse =
+        from_binary(&contract.query(deps.as_ref(), mock_env(), query).unwrap()).unwrap();
+    assert_eq!(redemption.status, RedemptionStatus::Redeemed);
+    assert!(redemption.burned);
+    assert!(contract.tokens.may_load(&deps.storage, TOKEN_ID).unwrap().is_none());
+
+    // the id of a redeemed token is never handed out again
+    let err = mint(&contract, deps.as_mut(), TOKEN_ID, None).unwrap_err();
+    assert_eq!(err, ContractError::Claimed {});
+}
+
+#[test]
+fn assigned_ids_skip_redeemed_tokens() {
+    let contract = Contract::default();
+    let mut deps = setup_redemption(&contract);
+    confirm_redemption(&contract, deps.as_mut(), true).unwrap();
+
+    let msg = MintMsg {
+        token_id: None,
+        ..mint_msg(TOKEN_ID)
+    };
+    mint_as(&contract, deps.as_mut(), MINTER, msg).unwrap();
+    assert!(contract.tokens.may_load(&deps.storage, TOKEN_ID).unwrap().is_none());
+    assert!(contract.tokens.may_load(&deps.storage, "2").unwrap().is_some());
+}
+
+#[test]
+fn redeemed_token_stays_locked_when_kept() {
+    let contract = Contract::default();
+    let mut deps = setup_redemption(&contract);
+
+    confirm_redemption(&contract, deps.as_mut(), false).unwrap();
+    let err = transfer(&contract, deps.as_mut()).unwrap_err();
+    assert_eq!(err, ContractError::AlreadyRedeemed {});
+    let msg: ExecuteMsg<Extension> = ExecuteMsg::RequestRedemption {
+        token_id: TOKEN_ID.to_string(),
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg)
+        .unwrap_err();
+    assert_eq!(err, ContractError::AlreadyRedeemed {});
+}
+
+#[test]
+fn cancelled_redemption_unlocks_token() {
+    let contract = Contract::default();
+    let mut deps = setup_redemption(&contract);
+
+    let msg: ExecuteMsg<Extension> = ExecuteMsg::CancelRedemption {
+        token_id: TOKEN_ID.to_string(),
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), mock_info(DESIGNER, &[]), msg.clone())
+        .unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    contract
+        .execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg)
+        .unwrap();
+
+    let err = confirm_redemption(&contract, deps.as_mut(), true).unwrap_err();
+    assert_eq!(err, ContractError::NoPendingRedemption {});
+    transfer(&contract, deps.as_mut()).unwrap();
+}
+
+fn freeze(contract: &Contract, deps: DepsMut, sender: &str, reason: &str) -> Result<Response, ContractError> {
+    let msg = ExecuteMsg::FreezeToken {
+        token_id: TOKEN_ID.to_string(),
+        reason: reason.to_string(),
+    };
+    contract.execute(deps, mock_env(), mock_info(sender, &[]), msg)
+}
+
+fn unfreeze(contract: &Contract, deps: DepsMut) -> Result<Response, ContractError> {
+    let msg = ExecuteMsg::UnfreezeToken {
+        token_id: TOKEN_ID.to_string(),
+    };
+    contract.execute(deps, mock_env(), mock_info(CREATOR, &[]), msg)
+}
+
+#[test]
+fn frozen_token_is_locked() {
+    let contract = Contract::default();
+    let mut deps = setup(&contract);
+    let frozen = ContractError::TokenFrozen {
+        reason: "reported stolen".to_string(),
+    };
+
+    let err = freeze(&contract, deps.as_mut(), OWNER, "reported stolen").unwrap_err();
+    assert_eq!(err, ContractError::Unauthorized {});
+    let err = freeze(&contract, deps.as_mut(), CREATOR, "").unwrap_err();
+    assert_eq!(err, ContractError::InvalidFreezeReason {});
+    freeze(&contract, deps.as_mut(), CREATOR, "reported stolen").unwrap();
+
+    let query = QueryMsg::FrozenStatus {
+        token_id: TOKEN_ID.to_string(),
+    };
+    let status: FrozenStatusResponse =
+        from_binary(&contract.query(deps.as_ref(), mock_env(), query).unwrap()).unwrap();
+    assert!(status.frozen);
+    assert_eq!(status.reason, Some("reported stolen".to_string()));
+
+    let err = transfer(&contract, deps.as_mut()).unwrap_err();
+    assert_eq!(err, frozen);
+    let msg = ExecuteMsg::Burn {
+        token_id: TOKEN_ID.to_string(),
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg)
+        .unwrap_err();
+    assert_eq!(err, frozen);
+    let msg = ExecuteMsg::RequestRedemption {
+        token_id: TOKEN_ID.to_string(),
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg)
+        .unwrap_err();
+    assert_eq!(err, frozen);
+
+    unfreeze(&contract, deps.as_mut()).unwrap();
+    let err = unfreeze(&contract, deps.as_mut()).unwrap_err();
+    assert_eq!(err, ContractError::TokenNotFrozen {});
+    transfer(&contract, deps.as_mut()).unwrap();
+}
+
+#[test]
+fn freezing_holds_pending_redemption() {
+    let contract = Contract::default();
+    let mut deps = setup_redemption(&contract);
+    let frozen = ContractError::TokenFrozen {
+        reason: "counterfeit".to_string(),
+    };
+    freeze(&contract, deps.as_mut(), CREATOR, "counterfeit").unwrap();
+
+    let err = confirm_redemption(&contract, deps.as_mut(), true).unwrap_err();
+    assert_eq!(err, frozen);
+    let msg = ExecuteMsg::CancelRedemption {
+        token_id: TOKEN_ID.to_string(),
+    };
+    let err = contract
+        .execute(deps.as_mut(), mock_env(), mock_info(OWNER, &[]), msg)
+        .unwrap_err();
+    assert_eq!(err, frozen);
+
+    unfreeze(&contract, deps.as_mut()).unwrap();
+    confirm_redemption(&contract, deps.as_mut(), true).unwrap();
+}
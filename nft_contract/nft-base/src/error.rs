@@ -1,4 +1,4 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{Decimal, StdError};
 use thiserror::Error;
 use url::ParseError;
 
@@ -33,4 +33,70 @@ pub enum ContractError {
 
     #[error("Invalid base URI (must be an IPFS URI)")]
     InvalidBaseURI {},
+
+    #[error("Batch must contain between 1 and {max} items")]
+    InvalidBatchSize { max: u32 },
+
+    #[error("Royalty increase exceeds {max_increase} for this period")]
+    RoyaltyIncreaseTooLarge { max_increase: Decimal },
+
+    #[error("Royalty exceeds the collection royalty")]
+    RoyaltyExceedsCollection {},
+
+    #[error("Invalid royalty timelock")]
+    InvalidRoyaltyTimelock {},
+
+    #[error("No pending collection owner")]
+    NoPendingCollectionOwner {},
+
+    #[error("No pending minter")]
+    NoPendingMinter {},
+
+    #[error("Minter quota exceeded")]
+    MinterQuotaExceeded {},
+
+    #[error("Token is frozen: {reason}")]
+    TokenFrozen { reason: String },
+
+    #[error("Token is not frozen")]
+    TokenNotFrozen {},
+
+    #[error("Freeze reason must be between 1 and 256 characters")]
+    InvalidFreezeReason {},
+
+    #[error("Token has a pending redemption")]
+    RedemptionPending {},
+
+    #[error("Token was already redeemed")]
+    AlreadyRedeemed {},
+
+    #[error("Token has no pending redemption")]
+    NoPendingRedemption {},
+
+    #[error("Token is not transferable")]
+    NotTransferable {},
+
+    #[error("Model is retired")]
+    ModelRetired {},
+
+    #[error("Model still has tokens")]
+    ModelHasTokens {},
+
+    #[error("Invalid supply")]
+    InvalidSupply {},
+
+    #[error("Model is sold out")]
+    SoldOut {},
+
+    #[error("Size {size} is not available for this model")]
+    SizeNotAvailable { size: String },
+
+    #[error("Size {size} is sold out")]
+    SizeSoldOut { size: String },
+
+    #[error("Metadata is frozen")]
+    MetadataFrozen {},
+
+    #[error("Invalid metadata: {reason}")]
+    InvalidMetadata { reason: String },
 }
\ No newline at end of file
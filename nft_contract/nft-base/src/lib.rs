@@ -4,14 +4,17 @@ pub mod msg;
 pub mod state;
 mod query;
 
+#[cfg(test)]
+mod contract_tests;
+
 
 pub use crate::error::ContractError;
-pub use crate::msg::{ExecuteMsg, InstantiateMsg, MintMsg, MinterResponse, QueryMsg};
-pub use crate::state::NFTContract;
+pub use crate::msg::{ExecuteMsg, InstantiateMsg, MintMsg, MintResponse, MinterResponse, QueryMsg};
+pub use crate::state::{Metadata, NFTContract, Trait};
 use cosmwasm_std::Empty;
 
-// This is a simple type to let us handle empty extensions
-pub type Extension = Option<Empty>;
+// Tokens and models carry their shoe metadata on-chain
+pub type Extension = Option<Metadata>;
 
 pub mod entry {
     use super::*;
@@ -1,10 +1,12 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Binary, Decimal};
+use cosmwasm_std::{Binary, Decimal, Timestamp, Uint128};
 use cw721::Expiration;
 
-use crate::{state::{CollectionInfo, Approval, TokenInfo, ModelInfo}, ContractError};
+use crate::{state::{CollectionInfo, Approval, TokenInfo, ModelInfo, RedemptionStatus, RoyaltyTimelock, SizeSupply}, ContractError};
+
+pub const MAX_ROYALTY_RECIPIENTS: usize = 10;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
@@ -12,6 +14,9 @@ pub struct InstantiateMsg {
     pub symbol: String,
     pub minter: String,
     pub collection_info: CollectionInfo<RoyaltyInfoResponse>,
+    /// Defaults to a one week delay and at most +2% per week. The period must be non-zero
+    /// and max_increase between 0 and 20%
+    pub royalty_timelock: Option<RoyaltyTimelock>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -39,27 +44,154 @@ pub enum ExecuteMsg<T> {
 
     Mint(MintMsg<T>),
 
+    /// Mints a run of tokens of one model, all sharing the given extension
+    BatchMint(BatchMintMsg<T>),
+
+    /// Transfers several tokens of the sender to one recipient
+    BatchTransfer { recipient: String, token_ids: Vec<String> },
+
     CreateShoeModel(CreateShoeModelMsg<T>),
 
     Burn { token_id: String },
 
-    ModifyCollectionInfo {description: Option<String>, image: Option<String>, external_link: Option<String>, royalty_info: Option<RoyaltyInfoResponse> }
+    /// Locks a token against transfers, sends, approvals and burning. A pending redemption
+    /// is put on hold until the token is unfrozen. Only callable by the collection owner
+    FreezeToken { token_id: String, reason: String },
+
+    UnfreezeToken { token_id: String },
+
+    /// Asks for the physical pair of a token. The token is locked until the redemption is
+    /// confirmed or cancelled
+    RequestRedemption { token_id: String },
+
+    /// Withdraws a pending redemption. Only callable by its requester while the token is
+    /// not frozen
+    CancelRedemption { token_id: String },
+
+    /// Confirms the pair was shipped, burning the token or marking it redeemed.
+    /// Only callable by the redeemer while the token is not frozen
+    ConfirmRedemption { token_id: String, burn: bool },
+
+    /// Sets or clears the redeemer. Only callable by the collection owner
+    SetRedeemer { address: Option<String> },
+
+    /// Moves a non-transferable token to `recipient`, or back to the minter.
+    /// Only callable by the minter
+    RecallNft { token_id: String, recipient: Option<String> },
+
+    ModifyCollectionInfo {description: Option<String>, image: Option<String>, external_link: Option<String>, royalty_info: Option<RoyaltyInfoResponse> },
+
+    /// Proposes a new main minter, who has to accept with AcceptMinter
+    UpdateMinter { new_minter: String },
+
+    AcceptMinter {},
+
+    /// Gives up minting for good, removing the main minter and all additional minters
+    RenounceMinter {},
+
+    /// Authorizes an additional minter, optionally capped at `quota` mints
+    AddMinter { address: String, quota: Option<u64> },
+
+    RemoveMinter { address: String },
+
+    /// Changes the owner and/or the extension of a model
+    UpdateModel {
+        model_id: String,
+        owner: Option<String>,
+        extension: Option<T>,
+    },
+
+    /// Hands a model over to a new owner. Only callable by the model owner
+    TransferModel { model_id: String, recipient: String },
+
+    /// Sets or clears the royalty of a model, capped at the collection royalty in force.
+    /// Only callable by the model owner
+    UpdateModelRoyalty {
+        model_id: String,
+        royalty_info: Option<RoyaltyInfoResponse>,
+    },
+
+    /// Stops further mints of a model, existing tokens stay valid
+    RetireModel { model_id: String },
+
+    /// Removes a model that no token references, the id stays reserved once tokens were minted
+    DeleteModel { model_id: String },
+
+    /// Proposes a new collection owner, who has to accept with AcceptCollectionOwnership
+    TransferCollectionOwnership { new_owner: String },
+
+    AcceptCollectionOwnership {},
+
+    /// Changes the uri of a model. Tokens keep their uri until PropagateModelUri is called.
+    /// Only callable by the collection owner
+    UpdateModelUri { model_id: String, model_uri: String },
+
+    /// Copies the model uri to a batch of its tokens, skipping tokens with their own uri.
+    /// Call again with the returned `last_token_id` as start_after until it is empty.
+    /// Only callable by the collection owner
+    PropagateModelUri {
+        model_id: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    /// Sets a token's own uri. None resets it to the uri of its model. Only callable by the
+    /// collection owner
+    UpdateTokenUri { token_id: String, token_uri: Option<String> },
+
+    /// Makes all token and model uris permanent. Only callable by the collection owner,
+    /// cannot be undone
+    FreezeMetadata {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct MintMsg<T> {
-    pub token_id: String,
+    /// Leave empty to let the contract assign the next free id
+    pub token_id: Option<String>,
+    /// Prefixes an assigned id with the model id, as in `model_id-42`
+    pub prefix_model_id: Option<bool>,
     pub owner: String,
     pub model_id: String,
     pub size: String,
+    /// Overrides the uri inherited from the model
+    pub token_uri: Option<String>,
+    /// Capped at the collection royalty in force
+    pub royalty_info: Option<RoyaltyInfoResponse>,
+    /// Overrides the transferable flag of the model
+    pub transferable: Option<bool>,
     pub extension: T,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BatchMintMsg<T> {
+    pub model_id: String,
+    pub items: Vec<BatchMintItem>,
+    /// Prefixes assigned ids with the model id, as in `model_id-42`
+    pub prefix_model_id: Option<bool>,
+    pub extension: T,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BatchMintItem {
+    /// Leave empty to let the contract assign the next free id
+    pub token_id: Option<String>,
+    pub owner: String,
+    pub size: String,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct CreateShoeModelMsg<T> {
     pub model_id: String,
     pub owner: String,
     pub model_uri: String,
+    /// Capped at the collection royalty in force
+    pub royalty_info: Option<RoyaltyInfoResponse>,
+    /// Total number of pairs that can ever be minted, None for unlimited
+    pub max_supply: Option<u64>,
+    /// Pairs available per size. When set, only these sizes can be minted
+    pub size_supply: Option<Vec<SizeSupply>>,
+    /// Tokens of the model are transferable unless set to false
+    pub transferable: Option<bool>,
     pub extension: T,
 }
 
@@ -93,12 +225,23 @@ pub enum QueryMsg {
 
     NumModels {},
 
+    /// Number of existing tokens of a model, optionally only of one size
+    NumTokensByModel {
+        model_id: String,
+        size: Option<String>,
+    },
+
     ContractInfo {},
  
     NftInfo {
         token_id: String,
     },
 
+    /// Same as `NftInfo` with the shoe fields at the top level
+    ShoeInfo {
+        token_id: String,
+    },
+
     AllNftInfo {
         token_id: String,
         include_expired: Option<bool>,
@@ -108,12 +251,44 @@ pub enum QueryMsg {
         model_id: String,
     },
 
+    ModelSupply {
+        model_id: String,
+    },
+
+    TokenStatus {
+        token_id: String,
+    },
+
+    /// Also answers for tokens burned on redemption
+    Redemption {
+        token_id: String,
+    },
+
+    Redeemer {},
+
+    FrozenStatus {
+        token_id: String,
+    },
+
     Tokens {
         owner: String,
         start_after: Option<String>,
         limit: Option<u32>,
     },
 
+    TokensByModel {
+        model_id: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    TokensByModelAndSize {
+        model_id: String,
+        size: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
     AllTokens {
         start_after: Option<String>,
         limit: Option<u32>,
@@ -129,19 +304,95 @@ pub enum QueryMsg {
         limit: Option<u32>,
     },
 
+    ModelsByOwner {
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
     AllModelsInfo {
         start_after: Option<String>,
         limit: Option<u32>,
     },
 
+    TokensByTrait {
+        trait_type: String,
+        value: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
+    ModelsByTrait {
+        trait_type: String,
+        value: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+
     Minter {},
 
     CollectionInfo {},
+
+    CollectionOwner {},
+
+    /// Current collection royalty and a pending change with the time it takes effect
+    RoyaltySchedule {},
+
+    /// Royalty paid on sales of this token, looked up on the token, then its model, then the collection
+    EffectiveRoyalty {
+        token_id: String,
+    },
+
+    /// Royalty owed to each recipient when this token is sold for `sale_price`.
+    /// CW2981 only reports a single receiver, see `Extension`
+    RoyaltyPayments {
+        token_id: String,
+        sale_price: Uint128,
+    },
+
+    /// CW2981 royalty queries
+    Extension {
+        msg: Cw2981QueryMsg,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw2981QueryMsg {
+    /// Royalty owed to the creator when this token is sold for `sale_price`
+    RoyaltyInfo {
+        token_id: String,
+        sale_price: Uint128,
+    },
+    /// Lets marketplaces check whether this contract pays royalties
+    CheckRoyalties {},
+}
+
+/// Set as response data of Mint
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct MintResponse {
+    pub token_id: String,
+}
+
+/// Set as response data of BatchMint
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct BatchMintResponse {
+    pub token_ids: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct MinterResponse {
-    pub minter: String,
+    /// None once minting was renounced
+    pub minter: Option<String>,
+    pub pending_minter: Option<String>,
+    pub minters: Vec<MinterQuotaResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct MinterQuotaResponse {
+    pub address: String,
+    pub quota: Option<u64>,
+    pub minted: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -151,21 +402,74 @@ pub struct CollectionInfoResponse {
     pub image: String,
     pub external_link: Option<String>,
     pub royalty_info: Option<RoyaltyInfoResponse>,
+    pub metadata_frozen: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoyaltyScheduleResponse {
+    pub current: Option<RoyaltyInfoResponse>,
+    pub pending: Option<RoyaltyInfoResponse>,
+    pub effective_at: Option<Timestamp>,
+    pub timelock: RoyaltyTimelock,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CollectionOwnerResponse {
+    pub owner: String,
+    pub pending_owner: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct RoyaltyInfoResponse {
+    pub recipients: Vec<RoyaltyRecipientResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoyaltyRecipientResponse {
     pub payment_address: String,
     pub share: Decimal,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EffectiveRoyaltyResponse {
+    pub royalty_info: Option<RoyaltyInfoResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoyaltiesInfoResponse {
+    pub address: String,
+    pub royalty_amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoyaltyPaymentsResponse {
+    pub payments: Vec<RoyaltiesInfoResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CheckRoyaltiesResponse {
+    pub royalty_payments: bool,
+}
+
 impl RoyaltyInfoResponse {
+    /// Returns the total share paid across all recipients
     pub fn share_validate(&self) -> Result<Decimal, ContractError> {
-        if self.share > Decimal::percent(20) {
+        if self.recipients.is_empty() || self.recipients.len() > MAX_ROYALTY_RECIPIENTS {
+            return Err(ContractError::InvalidRoyalities {});
+        }
+
+        let mut total_share = Decimal::zero();
+        for recipient in &self.recipients {
+            if recipient.share.is_zero() {
+                return Err(ContractError::InvalidRoyalities {});
+            }
+            total_share += recipient.share;
+        }
+        if total_share > Decimal::percent(20) {
             return Err(ContractError::InvalidRoyalities {});
         }
 
-        Ok(self.share)
+        Ok(total_share)
     }
 }
 
@@ -213,8 +517,22 @@ pub struct AllNftInfoResponse<T> {
     pub info: NftInfoResponse<T>,
 }
 
+/// cw721 `NftInfo` response, the shoe fields live in the extension
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct NftInfoResponse<T> {
+    pub token_uri: Option<String>,
+    pub extension: ShoeExtension<T>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ShoeExtension<T> {
+    pub model_id: String,
+    pub size: String,
+    pub extension: T,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ShoeInfoResponse<T> {
     pub model_id: String,
     pub token_uri: String,
     pub size: String,
@@ -225,9 +543,61 @@ pub struct NftInfoResponse<T> {
 pub struct ModelInfoResponse<T> {
     pub owner: String,
     pub model_uri: String,
+    pub royalty_info: Option<RoyaltyInfoResponse>,
+    pub max_supply: Option<u64>,
+    pub size_supply: Vec<SizeSupply>,
+    pub retired: bool,
+    pub transferable: bool,
     pub extension: T,
 }
 
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct TokenStatusResponse {
+    pub transferable: bool,
+    pub redemption: Option<RedemptionStatus>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RedemptionResponse {
+    pub status: RedemptionStatus,
+    pub requested_by: String,
+    pub requested_at: Timestamp,
+    pub redeemed_at: Option<Timestamp>,
+    pub burned: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct FrozenStatusResponse {
+    pub frozen: bool,
+    pub reason: Option<String>,
+    pub frozen_at: Option<Timestamp>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct RedeemerResponse {
+    pub redeemer: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct ModelSupplyResponse {
+    pub model_id: String,
+    pub max_supply: Option<u64>,
+    pub minted: u64,
+    pub burned: u64,
+    /// None when the model has no cap. Burned pairs do not free up supply
+    pub remaining: Option<u64>,
+    pub sizes: Vec<SizeSupplyResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct SizeSupplyResponse {
+    pub size: String,
+    pub max_supply: Option<u64>,
+    pub minted: u64,
+    pub burned: u64,
+    pub remaining: Option<u64>,
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct TokensResponse {
     pub tokens: Vec<String>,
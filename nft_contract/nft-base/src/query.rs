@@ -1,7 +1,7 @@
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
-use cosmwasm_std::{to_binary, Addr, Binary, BlockInfo, Deps, Env, Order, StdError, StdResult, CustomMsg};
+use cosmwasm_std::{to_binary, Addr, Binary, BlockInfo, Deps, Env, Order, StdError, StdResult, CustomMsg, Uint128};
 
 use cw721::Expiration;
 use cw_storage_plus::Bound;
@@ -9,25 +9,45 @@ use cw_utils::maybe_addr;
 
 use crate::msg::{
     AllModelsResponse, AllNftInfoResponse, AllNftsResponse, ApprovalResponse, ApprovalsResponse,
-    CollectionInfoResponse, ContractInfoResponse, MinterResponse, ModelInfoResponse,
-    ModelsResponse, NftInfoResponse, NumModelsResponse, NumTokensResponse, OperatorsResponse,
-    OwnerOfResponse, QueryMsg, RoyaltyInfoResponse, TokensResponse,
+    CheckRoyaltiesResponse, CollectionInfoResponse, CollectionOwnerResponse, ContractInfoResponse,
+    Cw2981QueryMsg, EffectiveRoyaltyResponse, FrozenStatusResponse, MinterQuotaResponse, MinterResponse,
+    ModelInfoResponse, ModelSupplyResponse, ModelsResponse, NftInfoResponse, NumModelsResponse,
+    NumTokensResponse, OperatorsResponse, OwnerOfResponse, QueryMsg, RoyaltiesInfoResponse,
+    RoyaltyInfoResponse, RoyaltyPaymentsResponse, RoyaltyRecipientResponse,
+    RoyaltyScheduleResponse, ShoeExtension, ShoeInfoResponse, SizeSupplyResponse,
+    RedeemerResponse, RedemptionResponse, TokenStatusResponse, TokensResponse,
 };
-use crate::state::COLLECTION_INFO;
-use crate::state::{NFTContract, Approval, ModelInfo, TokenInfo};
+use crate::state::{
+    collection_royalty, COLLECTION_INFO, COLLECTION_OWNER, FROZEN_TOKENS, METADATA_FROZEN, MINTERS,
+    MODEL_SUPPLY, MODEL_TRAITS, PENDING_COLLECTION_OWNER, PENDING_MINTER, PENDING_ROYALTY,
+    REDEEMER, REDEMPTIONS, ROYALTY_TIMELOCK, SIZE_SUPPLY, TOKEN_TRAITS,
+};
+use crate::state::{NFTContract, Approval, MetadataExtension, ModelInfo, RoyaltyInfo, TokenInfo};
 
 const DEFAULT_LIMIT: u32 = 10;
 const MAX_LIMIT: u32 = 30;
 
 impl<'a, T, C> NFTContract<'a, T, C>
 where
-    T: Serialize + DeserializeOwned + Clone,
+    T: Serialize + DeserializeOwned + Clone + MetadataExtension,
     C: CustomMsg,
 {
     pub fn minter(&self, deps: Deps) -> StdResult<MinterResponse> {
-        let minter_addr = self.minter.load(deps.storage)?;
+        let minters = MINTERS
+            .range(deps.storage, None, None, Order::Ascending)
+            .map(|item| {
+                item.map(|(address, quota)| MinterQuotaResponse {
+                    address: address.to_string(),
+                    quota: quota.quota,
+                    minted: quota.minted,
+                })
+            })
+            .collect::<StdResult<Vec<_>>>()?;
+
         Ok(MinterResponse {
-            minter: minter_addr.to_string(),
+            minter: self.minter.may_load(deps.storage)?.map(|addr| addr.to_string()),
+            pending_minter: PENDING_MINTER.may_load(deps.storage)?.map(|addr| addr.to_string()),
+            minters,
         })
     }
 
@@ -40,6 +60,23 @@ where
         Ok(NumTokensResponse { count })
     }
 
+    fn num_tokens_by_model(
+        &self,
+        deps: Deps,
+        model_id: String,
+        size: Option<String>,
+    ) -> StdResult<NumTokensResponse> {
+        let supply = match size {
+            Some(size) => SIZE_SUPPLY.may_load(deps.storage, (&model_id, &size))?,
+            None => MODEL_SUPPLY.may_load(deps.storage, &model_id)?,
+        }
+        .unwrap_or_default();
+        Ok(NumTokensResponse {
+            // tokens minted before the counters existed may have been burned since
+            count: supply.minted.saturating_sub(supply.burned),
+        })
+    }
+
     fn num_models(&self, deps: Deps) -> StdResult<NumModelsResponse> {
         let count = self.model_count(deps.storage)?;
         Ok(NumModelsResponse { count })
@@ -61,7 +98,12 @@ where
 
     fn nft_info(&self, deps: Deps, token_id: String) -> StdResult<NftInfoResponse<T>> {
         let info = self.tokens.load(deps.storage, &token_id)?;
-        Ok(NftInfoResponse {
+        Ok(parse_nft_info(info))
+    }
+
+    fn shoe_info(&self, deps: Deps, token_id: String) -> StdResult<ShoeInfoResponse<T>> {
+        let info = self.tokens.load(deps.storage, &token_id)?;
+        Ok(ShoeInfoResponse {
             model_id: info.model_id,
             token_uri: info.token_uri,
             size: info.size,
@@ -82,12 +124,7 @@ where
                 owner: info.owner.to_string(),
                 approvals: humanize_approvals(&env.block, &info, include_expired),
             },
-            info: NftInfoResponse {
-                model_id: info.model_id,
-                token_uri: info.token_uri,
-                size: info.size,
-                extension: info.extension,
-            },
+            info: parse_nft_info(info),
         })
     }
 
@@ -96,10 +133,147 @@ where
         Ok(ModelInfoResponse {
             owner: info.owner.to_string(),
             model_uri: info.model_uri,
+            royalty_info: info.royalty_info.map(humanize_royalty_info),
+            max_supply: info.max_supply,
+            size_supply: info.size_supply,
+            retired: info.retired,
+            transferable: info.transferable,
             extension: info.extension,
         })
     }
 
+    fn token_status(&self, deps: Deps, token_id: String) -> StdResult<TokenStatusResponse> {
+        let token = self.tokens.load(deps.storage, &token_id)?;
+        let redemption = REDEMPTIONS.may_load(deps.storage, &token_id)?;
+        Ok(TokenStatusResponse {
+            transferable: token.transferable,
+            redemption: redemption.map(|redemption| redemption.status),
+        })
+    }
+
+    fn model_supply(&self, deps: Deps, model_id: String) -> StdResult<ModelSupplyResponse> {
+        let info = self.models.load(deps.storage, &model_id)?;
+        let supply = MODEL_SUPPLY.may_load(deps.storage, &model_id)?.unwrap_or_default();
+
+        let mut sizes: Vec<SizeSupplyResponse> = info
+            .size_supply
+            .iter()
+            .map(|size_supply| SizeSupplyResponse {
+                size: size_supply.size.clone(),
+                max_supply: Some(size_supply.quantity),
+                minted: 0,
+                burned: 0,
+                remaining: Some(size_supply.quantity),
+            })
+            .collect();
+        for item in SIZE_SUPPLY
+            .prefix(&model_id)
+            .range(deps.storage, None, None, Order::Ascending)
+        {
+            let (size, size_counter) = item?;
+            match sizes.iter_mut().find(|s| s.size == size) {
+                Some(s) => {
+                    s.minted = size_counter.minted;
+                    s.burned = size_counter.burned;
+                    s.remaining = s.max_supply.map(|max| max.saturating_sub(size_counter.minted));
+                }
+                None => sizes.push(SizeSupplyResponse {
+                    size,
+                    max_supply: None,
+                    minted: size_counter.minted,
+                    burned: size_counter.burned,
+                    remaining: None,
+                }),
+            }
+        }
+
+        Ok(ModelSupplyResponse {
+            model_id,
+            max_supply: info.max_supply,
+            minted: supply.minted,
+            burned: supply.burned,
+            remaining: info.max_supply.map(|max| max.saturating_sub(supply.minted)),
+            sizes,
+        })
+    }
+
+    fn load_effective_royalty(
+        &self,
+        deps: Deps,
+        block: &BlockInfo,
+        token_id: &str,
+    ) -> StdResult<Option<RoyaltyInfo>> {
+        let token = self.tokens.load(deps.storage, token_id)?;
+        let royalty_info = match token.royalty_info {
+            Some(royalty_info) => Some(royalty_info),
+            None => match self.models.load(deps.storage, &token.model_id)?.royalty_info {
+                Some(royalty_info) => Some(royalty_info),
+                None => collection_royalty(deps.storage, block)?,
+            },
+        };
+        Ok(royalty_info)
+    }
+
+    fn effective_royalty(
+        &self,
+        deps: Deps,
+        env: Env,
+        token_id: String,
+    ) -> StdResult<EffectiveRoyaltyResponse> {
+        let royalty_info = self.load_effective_royalty(deps, &env.block, &token_id)?;
+        Ok(EffectiveRoyaltyResponse {
+            royalty_info: royalty_info.map(humanize_royalty_info),
+        })
+    }
+
+    fn royalty_payments(
+        &self,
+        deps: Deps,
+        env: Env,
+        token_id: String,
+        sale_price: Uint128,
+    ) -> StdResult<RoyaltyPaymentsResponse> {
+        let payments = match self.load_effective_royalty(deps, &env.block, &token_id)? {
+            Some(royalty_info) => royalty_info
+                .recipients
+                .into_iter()
+                .map(|recipient| RoyaltiesInfoResponse {
+                    address: recipient.payment_address.to_string(),
+                    royalty_amount: sale_price * recipient.share,
+                })
+                .collect(),
+            None => vec![],
+        };
+        Ok(RoyaltyPaymentsResponse { payments })
+    }
+
+    /// CW2981 has a single receiver, so the whole royalty is reported against the first recipient
+    fn royalty_info(
+        &self,
+        deps: Deps,
+        env: Env,
+        token_id: String,
+        sale_price: Uint128,
+    ) -> StdResult<RoyaltiesInfoResponse> {
+        let payments = self.royalty_payments(deps, env, token_id, sale_price)?.payments;
+        match payments.first() {
+            Some(first) => Ok(RoyaltiesInfoResponse {
+                address: first.address.clone(),
+                royalty_amount: payments.iter().map(|payment| payment.royalty_amount).sum(),
+            }),
+            None => Ok(RoyaltiesInfoResponse {
+                address: String::new(),
+                royalty_amount: Uint128::zero(),
+            }),
+        }
+    }
+
+    fn check_royalties(&self) -> StdResult<CheckRoyaltiesResponse> {
+        Ok(CheckRoyaltiesResponse {
+            royalty_payments: true,
+        })
+    }
+
     /// operators returns all operators owner given access to
     fn operators(
         &self,
@@ -213,6 +387,51 @@ where
         Ok(TokensResponse { tokens })
     }
 
+    fn tokens_by_model(
+        &self,
+        deps: Deps,
+        model_id: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<TokensResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+
+        let tokens = self
+            .tokens
+            .idx
+            .model
+            .prefix(model_id)
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(TokensResponse { tokens })
+    }
+
+    fn tokens_by_model_and_size(
+        &self,
+        deps: Deps,
+        model_id: String,
+        size: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<TokensResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+
+        let tokens = self
+            .tokens
+            .idx
+            .model_size
+            .prefix((model_id, size))
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(TokensResponse { tokens })
+    }
+
     fn all_tokens(
         &self,
         deps: Deps,
@@ -272,6 +491,30 @@ where
         Ok(ModelsResponse { models: models? })
     }
 
+    fn models_by_owner(
+        &self,
+        deps: Deps,
+        owner: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<ModelsResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.map(|s| Bound::ExclusiveRaw(s.into()));
+
+        let owner_addr = deps.api.addr_validate(&owner)?;
+        let models: Vec<String> = self
+            .models
+            .idx
+            .owner
+            .prefix(owner_addr)
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .map(|x| x.map(|addr| addr.to_string()))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        Ok(ModelsResponse { models })
+    }
+
     fn all_models_info(
         &self,
         deps: Deps,
@@ -293,11 +536,52 @@ where
         })
     }
 
+    fn tokens_by_trait(
+        &self,
+        deps: Deps,
+        trait_type: String,
+        value: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<TokensResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.as_deref().map(Bound::exclusive);
+
+        let tokens: StdResult<Vec<String>> = TOKEN_TRAITS
+            .prefix((&trait_type, &value))
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect();
+
+        Ok(TokensResponse { tokens: tokens? })
+    }
+
+    fn models_by_trait(
+        &self,
+        deps: Deps,
+        trait_type: String,
+        value: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<ModelsResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        let start = start_after.as_deref().map(Bound::exclusive);
+
+        let models: StdResult<Vec<String>> = MODEL_TRAITS
+            .prefix((&trait_type, &value))
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect();
+
+        Ok(ModelsResponse { models: models? })
+    }
+
     pub fn query(&self, deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         match msg {
             QueryMsg::Minter {} => to_binary(&self.minter(deps)?),
             QueryMsg::ContractInfo {} => to_binary(&self.contract_info(deps)?),
             QueryMsg::NftInfo { token_id } => to_binary(&self.nft_info(deps, token_id)?),
+            QueryMsg::ShoeInfo { token_id } => to_binary(&self.shoe_info(deps, token_id)?),
             QueryMsg::OwnerOf {
                 token_id,
                 include_expired,
@@ -314,6 +598,13 @@ where
                 include_expired.unwrap_or(false),
             )?),
             QueryMsg::ModelInfo { model_id } => to_binary(&self.model_info(deps, model_id)?),
+            QueryMsg::ModelSupply { model_id } => to_binary(&self.model_supply(deps, model_id)?),
+            QueryMsg::TokenStatus { token_id } => to_binary(&self.token_status(deps, token_id)?),
+            QueryMsg::Redemption { token_id } => to_binary(&query_redemption(deps, token_id)?),
+            QueryMsg::Redeemer {} => to_binary(&query_redeemer(deps)?),
+            QueryMsg::FrozenStatus { token_id } => {
+                to_binary(&query_frozen_status(deps, token_id)?)
+            }
             QueryMsg::AllOperators {
                 owner,
                 include_expired,
@@ -329,11 +620,25 @@ where
             )?),
             QueryMsg::NumTokens {} => to_binary(&self.num_tokens(deps)?),
             QueryMsg::NumModels {} => to_binary(&self.num_models(deps)?),
+            QueryMsg::NumTokensByModel { model_id, size } => {
+                to_binary(&self.num_tokens_by_model(deps, model_id, size)?)
+            }
             QueryMsg::Tokens {
                 owner,
                 start_after,
                 limit,
             } => to_binary(&self.tokens(deps, owner, start_after, limit)?),
+            QueryMsg::TokensByModel {
+                model_id,
+                start_after,
+                limit,
+            } => to_binary(&self.tokens_by_model(deps, model_id, start_after, limit)?),
+            QueryMsg::TokensByModelAndSize {
+                model_id,
+                size,
+                start_after,
+                limit,
+            } => to_binary(&self.tokens_by_model_and_size(deps, model_id, size, start_after, limit)?),
             QueryMsg::AllTokens { start_after, limit } => {
                 to_binary(&self.all_tokens(deps, start_after, limit)?)
             }
@@ -343,9 +648,26 @@ where
             QueryMsg::AllModels { start_after, limit } => {
                 to_binary(&self.all_models(deps, start_after, limit)?)
             }
+            QueryMsg::ModelsByOwner {
+                owner,
+                start_after,
+                limit,
+            } => to_binary(&self.models_by_owner(deps, owner, start_after, limit)?),
             QueryMsg::AllModelsInfo { start_after, limit } => {
                 to_binary(&self.all_models_info(deps, start_after, limit)?)
             }
+            QueryMsg::TokensByTrait {
+                trait_type,
+                value,
+                start_after,
+                limit,
+            } => to_binary(&self.tokens_by_trait(deps, trait_type, value, start_after, limit)?),
+            QueryMsg::ModelsByTrait {
+                trait_type,
+                value,
+                start_after,
+                limit,
+            } => to_binary(&self.models_by_trait(deps, trait_type, value, start_after, limit)?),
             QueryMsg::Approval {
                 token_id,
                 spender,
@@ -363,21 +685,32 @@ where
             } => {
                 to_binary(&self.approvals(deps, env, token_id, include_expired.unwrap_or(false))?)
             }
-            QueryMsg::CollectionInfo {} => to_binary(&query_config(deps)?),
+            QueryMsg::CollectionInfo {} => to_binary(&query_config(deps, env)?),
+            QueryMsg::RoyaltySchedule {} => to_binary(&query_royalty_schedule(deps, env)?),
+            QueryMsg::CollectionOwner {} => to_binary(&query_collection_owner(deps)?),
+            QueryMsg::EffectiveRoyalty { token_id } => {
+                to_binary(&self.effective_royalty(deps, env, token_id)?)
+            }
+            QueryMsg::RoyaltyPayments {
+                token_id,
+                sale_price,
+            } => to_binary(&self.royalty_payments(deps, env, token_id, sale_price)?),
+            QueryMsg::Extension { msg } => match msg {
+                Cw2981QueryMsg::RoyaltyInfo {
+                    token_id,
+                    sale_price,
+                } => to_binary(&self.royalty_info(deps, env, token_id, sale_price)?),
+                Cw2981QueryMsg::CheckRoyalties {} => to_binary(&self.check_royalties()?),
+            },
         }
     }
 }
 
-fn query_config(deps: Deps) -> StdResult<CollectionInfoResponse> {
+fn query_config(deps: Deps, env: Env) -> StdResult<CollectionInfoResponse> {
     let info = COLLECTION_INFO.load(deps.storage)?;
 
-    let royalty_info_res: Option<RoyaltyInfoResponse> = match info.royalty_info {
-        Some(royalty_info) => Some(RoyaltyInfoResponse {
-            payment_address: royalty_info.payment_address.to_string(),
-            share: royalty_info.share,
-        }),
-        None => None,
-    };
+    let royalty_info_res: Option<RoyaltyInfoResponse> =
+        collection_royalty(deps.storage, &env.block)?.map(humanize_royalty_info);
 
     Ok(CollectionInfoResponse {
         creator: info.creator,
@@ -385,6 +718,62 @@ fn query_config(deps: Deps) -> StdResult<CollectionInfoResponse> {
         image: info.image,
         external_link: info.external_link,
         royalty_info: royalty_info_res,
+        metadata_frozen: METADATA_FROZEN.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
+fn query_royalty_schedule(deps: Deps, env: Env) -> StdResult<RoyaltyScheduleResponse> {
+    let current = collection_royalty(deps.storage, &env.block)?;
+    let pending = PENDING_ROYALTY
+        .may_load(deps.storage)?
+        .filter(|pending| env.block.time < pending.effective_at);
+
+    Ok(RoyaltyScheduleResponse {
+        current: current.map(humanize_royalty_info),
+        effective_at: pending.as_ref().map(|pending| pending.effective_at),
+        pending: pending.and_then(|pending| pending.royalty_info.map(humanize_royalty_info)),
+        timelock: ROYALTY_TIMELOCK.load(deps.storage)?,
+    })
+}
+
+fn query_redemption(deps: Deps, token_id: String) -> StdResult<RedemptionResponse> {
+    let redemption = REDEMPTIONS.load(deps.storage, &token_id)?;
+    Ok(RedemptionResponse {
+        status: redemption.status,
+        requested_by: redemption.requested_by.to_string(),
+        requested_at: redemption.requested_at,
+        redeemed_at: redemption.redeemed_at,
+        burned: redemption.burned,
+    })
+}
+
+fn query_frozen_status(deps: Deps, token_id: String) -> StdResult<FrozenStatusResponse> {
+    Ok(match FROZEN_TOKENS.may_load(deps.storage, &token_id)? {
+        Some(frozen) => FrozenStatusResponse {
+            frozen: true,
+            reason: Some(frozen.reason),
+            frozen_at: Some(frozen.frozen_at),
+        },
+        None => FrozenStatusResponse {
+            frozen: false,
+            reason: None,
+            frozen_at: None,
+        },
+    })
+}
+
+fn query_redeemer(deps: Deps) -> StdResult<RedeemerResponse> {
+    Ok(RedeemerResponse {
+        redeemer: REDEEMER.may_load(deps.storage)?.map(|addr| addr.to_string()),
+    })
+}
+
+fn query_collection_owner(deps: Deps) -> StdResult<CollectionOwnerResponse> {
+    Ok(CollectionOwnerResponse {
+        owner: COLLECTION_OWNER.load(deps.storage)?.to_string(),
+        pending_owner: PENDING_COLLECTION_OWNER
+            .may_load(deps.storage)?
+            .map(|addr| addr.to_string()),
     })
 }
 
@@ -395,6 +784,17 @@ fn parse_approval(item: StdResult<(Addr, Expiration)>) -> StdResult<Approval> {
     })
 }
 
+fn parse_nft_info<T>(info: TokenInfo<T>) -> NftInfoResponse<T> {
+    NftInfoResponse {
+        token_uri: Some(info.token_uri),
+        extension: ShoeExtension {
+            model_id: info.model_id,
+            size: info.size,
+            extension: info.extension,
+        },
+    }
+}
+
 fn humanize_approvals<T>(
     block: &BlockInfo,
     info: &TokenInfo<T>,
@@ -412,4 +812,17 @@ fn humanize_approval(approval: &Approval) -> Approval {
         spender: approval.spender.clone(),
         expires: approval.expires,
     }
-}
\ No newline at end of file
+}
+
+fn humanize_royalty_info(royalty_info: RoyaltyInfo) -> RoyaltyInfoResponse {
+    RoyaltyInfoResponse {
+        recipients: royalty_info
+            .recipients
+            .into_iter()
+            .map(|recipient| RoyaltyRecipientResponse {
+                payment_address: recipient.payment_address.to_string(),
+                share: recipient.share,
+            })
+            .collect(),
+    }
+}
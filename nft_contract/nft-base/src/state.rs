@@ -4,12 +4,19 @@ use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use std::marker::PhantomData;
 
-use cosmwasm_std::{Addr, BlockInfo, Decimal, StdResult, Storage};
+use cosmwasm_std::{Addr, BlockInfo, Decimal, Empty, StdResult, Storage, Timestamp};
 use cw721::Expiration;
+use url::Url;
 
 use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex};
 
 use crate::msg::{ContractInfoResponse};
+use crate::ContractError;
+
+const MAX_NAME_LENGTH: usize = 128;
+const MAX_DESCRIPTION_LENGTH: usize = 512;
+const MAX_TRAIT_LENGTH: usize = 64;
+const MAX_ATTRIBUTES: usize = 32;
 
 pub struct NFTContract<'a, T, C>
 where
@@ -40,6 +47,8 @@ where
             "operators",
             "tokens",
             "tokens__owner",
+            "tokens__model",
+            "tokens__model_size",
             "models",
             "models_owner",
         )
@@ -58,11 +67,15 @@ where
         operator_key: &'a str,
         tokens_key: &'a str,
         tokens_owner_key: &'a str,
+        tokens_model_key: &'a str,
+        tokens_model_size_key: &'a str,
         models_key: &'a str,
         models_owner_key: &'a str,
     ) -> Self {
         let indexes = TokenIndexes {
             owner: MultiIndex::new(token_owner_idx, tokens_key, tokens_owner_key),
+            model: MultiIndex::new(token_model_idx, tokens_key, tokens_model_key),
+            model_size: MultiIndex::new(token_model_size_idx, tokens_key, tokens_model_size_key),
         };
         let model_indexes = ModelIndexes {
             owner: MultiIndex::new(model_owner_idx, models_key, models_owner_key),
@@ -123,6 +136,19 @@ pub struct CollectionInfo<T> {
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct RoyaltyInfo {
+    pub recipients: Vec<RoyaltyRecipient>,
+}
+
+impl RoyaltyInfo {
+    pub fn total_share(&self) -> Decimal {
+        self.recipients
+            .iter()
+            .fold(Decimal::zero(), |total, recipient| total + recipient.share)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoyaltyRecipient {
     pub payment_address: Addr,
     pub share: Decimal,
 }
@@ -141,12 +167,23 @@ pub struct TokenInfo<T> {
     /// Id of this token's model
     pub model_id: String,
 
-    /// Token uri will be the same as model_uri of this token's model
+    /// Token uri defaults to the model_uri of this token's model
     pub token_uri: String,
 
+    /// Set when token_uri was given explicitly, so model uri changes leave it alone
+    #[serde(default)]
+    pub token_uri_override: bool,
+
     /// Here is a unique feature of Anone project.
     pub size: String,
 
+    /// Overrides the royalty of the model and the collection for this token
+    pub royalty_info: Option<RoyaltyInfo>,
+
+    /// Locked tokens can only be burned by their owner or recalled by the minter
+    #[serde(default = "default_transferable")]
+    pub transferable: bool,
+
     /// You can add any custom metadata here when you extend cw721-base
     pub extension: T,
 }
@@ -161,9 +198,237 @@ pub struct ModelInfo<T> {
 
     pub model_uri: String,
 
+    /// Overrides the collection royalty for tokens of this model
+    pub royalty_info: Option<RoyaltyInfo>,
+
+    /// Total number of pairs that can ever be minted, None for unlimited
+    #[serde(default)]
+    pub max_supply: Option<u64>,
+
+    /// Pairs available per size. When not empty, only these sizes can be minted
+    #[serde(default)]
+    pub size_supply: Vec<SizeSupply>,
+
+    /// Retired models can no longer be minted, existing tokens stay valid
+    #[serde(default)]
+    pub retired: bool,
+
+    /// Default for tokens of this model that do not set it themselves
+    #[serde(default = "default_transferable")]
+    pub transferable: bool,
+
     pub extension: T,
 }
 
+fn default_transferable() -> bool {
+    true
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SizeSupply {
+    pub size: String,
+    pub quantity: u64,
+}
+
+/// Rules for raising the collection royalty
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoyaltyTimelock {
+    /// Seconds between announcing a royalty increase and it taking effect
+    pub delay: u64,
+    /// Largest total share increase allowed within one period
+    pub max_increase: Decimal,
+    /// Length of the increase period in seconds
+    pub period: u64,
+}
+
+impl Default for RoyaltyTimelock {
+    fn default() -> Self {
+        RoyaltyTimelock {
+            delay: 7 * 24 * 60 * 60,
+            max_increase: Decimal::percent(2),
+            period: 7 * 24 * 60 * 60,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingRoyalty {
+    pub royalty_info: Option<RoyaltyInfo>,
+    pub effective_at: Timestamp,
+}
+
+/// Total royalty share at the start of the current increase period
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoyaltyIncreaseWindow {
+    pub start: Timestamp,
+    pub base_share: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FrozenToken {
+    pub reason: String,
+    pub frozen_at: Timestamp,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Redemption {
+    pub status: RedemptionStatus,
+    pub requested_by: Addr,
+    pub requested_at: Timestamp,
+    pub redeemed_at: Option<Timestamp>,
+    /// Whether the token was burned when the redemption was confirmed
+    pub burned: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RedemptionStatus {
+    /// Waiting for the redeemer to ship the pair, the token is locked meanwhile
+    Requested,
+    /// The pair was shipped, the token stays locked for good
+    Redeemed,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MinterQuota {
+    /// Maximum number of tokens this minter may mint, None for unlimited
+    pub quota: Option<u64>,
+    pub minted: u64,
+}
+
+/// Running mint and burn counters of a model or of one of its sizes
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct Supply {
+    pub minted: u64,
+    pub burned: u64,
+}
+
+/// On-chain metadata of a shoe token or model
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct Metadata {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub image: Option<String>,
+    pub colorway: Option<String>,
+    /// Release date formatted as YYYY-MM-DD
+    pub release_date: Option<String>,
+    pub attributes: Option<Vec<Trait>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Trait {
+    pub trait_type: String,
+    pub value: String,
+}
+
+/// Lets the contract validate token and model extensions and index them by trait
+pub trait MetadataExtension {
+    fn validate(&self) -> Result<(), ContractError>;
+
+    fn traits(&self) -> Vec<Trait>;
+}
+
+impl MetadataExtension for Option<Empty> {
+    fn validate(&self) -> Result<(), ContractError> {
+        Ok(())
+    }
+
+    fn traits(&self) -> Vec<Trait> {
+        vec![]
+    }
+}
+
+impl MetadataExtension for Option<Metadata> {
+    fn validate(&self) -> Result<(), ContractError> {
+        let metadata = match self {
+            Some(metadata) => metadata,
+            None => return Ok(()),
+        };
+
+        if metadata.name.as_ref().is_some_and(|name| name.len() > MAX_NAME_LENGTH) {
+            return Err(invalid_metadata("name too long"));
+        }
+        if metadata
+            .description
+            .as_ref()
+            .is_some_and(|description| description.len() > MAX_DESCRIPTION_LENGTH)
+        {
+            return Err(ContractError::DescriptionTooLong {});
+        }
+        if metadata.colorway.as_ref().is_some_and(|colorway| colorway.len() > MAX_NAME_LENGTH) {
+            return Err(invalid_metadata("colorway too long"));
+        }
+        if let Some(ref image) = metadata.image {
+            Url::parse(image)?;
+        }
+        if let Some(ref release_date) = metadata.release_date {
+            if !is_date(release_date) {
+                return Err(invalid_metadata("release_date must be YYYY-MM-DD"));
+            }
+        }
+
+        let attributes = metadata.attributes.as_deref().unwrap_or_default();
+        if attributes.len() > MAX_ATTRIBUTES {
+            return Err(invalid_metadata("too many attributes"));
+        }
+        for (i, attribute) in attributes.iter().enumerate() {
+            if attribute.trait_type.is_empty()
+                || attribute.value.is_empty()
+                || attribute.trait_type.len() > MAX_TRAIT_LENGTH
+                || attribute.value.len() > MAX_TRAIT_LENGTH
+            {
+                return Err(invalid_metadata("invalid attribute"));
+            }
+            if attributes[..i]
+                .iter()
+                .any(|other| other.trait_type == attribute.trait_type)
+            {
+                return Err(invalid_metadata("duplicate trait_type"));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn traits(&self) -> Vec<Trait> {
+        self.as_ref()
+            .and_then(|metadata| metadata.attributes.clone())
+            .unwrap_or_default()
+    }
+}
+
+fn invalid_metadata(reason: &str) -> ContractError {
+    ContractError::InvalidMetadata {
+        reason: reason.to_string(),
+    }
+}
+
+fn is_date(date: &str) -> bool {
+    let parts: Vec<&str> = date.split('-').collect();
+    if parts.len() != 3
+        || parts[0].len() != 4
+        || parts[1].len() != 2
+        || parts[2].len() != 2
+        || !parts.iter().all(|part| part.chars().all(|c| c.is_ascii_digit()))
+    {
+        return false;
+    }
+
+    let (year, month, day): (u32, u32, u32) = match (parts[0].parse(), parts[1].parse(), parts[2].parse()) {
+        (Ok(year), Ok(month), Ok(day)) => (year, month, day),
+        _ => return false,
+    };
+    let leap_year = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap_year => 29,
+        2 => 28,
+        _ => return false,
+    };
+    (1..=days_in_month).contains(&day)
+}
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct Approval {
     /// Account that can transfer/send the token
@@ -183,6 +448,8 @@ where
     T: Serialize + DeserializeOwned + Clone,
 {
     pub owner: MultiIndex<'a, Addr, TokenInfo<T>, Addr>,
+    pub model: MultiIndex<'a, String, TokenInfo<T>, String>,
+    pub model_size: MultiIndex<'a, (String, String), TokenInfo<T>, String>,
 }
 
 impl<'a, T> IndexList<TokenInfo<T>> for TokenIndexes<'a, T>
@@ -190,7 +457,7 @@ where
     T: Serialize + DeserializeOwned + Clone,
 {
     fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<TokenInfo<T>>> + '_> {
-        let v: Vec<&dyn Index<TokenInfo<T>>> = vec![&self.owner];
+        let v: Vec<&dyn Index<TokenInfo<T>>> = vec![&self.owner, &self.model, &self.model_size];
         Box::new(v.into_iter())
     }
 }
@@ -199,6 +466,14 @@ pub fn token_owner_idx<T>(_: &[u8], d: &TokenInfo<T>) -> Addr {
     d.owner.clone()
 }
 
+pub fn token_model_idx<T>(_: &[u8], d: &TokenInfo<T>) -> String {
+    d.model_id.clone()
+}
+
+pub fn token_model_size_idx<T>(_: &[u8], d: &TokenInfo<T>) -> (String, String) {
+    (d.model_id.clone(), d.size.clone())
+}
+
 pub struct ModelIndexes<'a, T>
 where
     T: Serialize + DeserializeOwned + Clone,
@@ -220,4 +495,42 @@ pub fn model_owner_idx<T>(_: &[u8],d: &ModelInfo<T>) -> Addr {
     d.owner.clone()
 }
 
-pub const COLLECTION_INFO: Item<CollectionInfo<RoyaltyInfo>> = Item::new("collection_info");
\ No newline at end of file
+pub const COLLECTION_INFO: Item<CollectionInfo<RoyaltyInfo>> = Item::new("collection_info");
+/// Minter proposed by UpdateMinter, waiting to accept
+pub const PENDING_MINTER: Item<Addr> = Item::new("pending_minter");
+/// Minters besides the main minter, with their mint quota
+pub const MINTERS: Map<&Addr, MinterQuota> = Map::new("minters");
+/// Last id handed out to a mint without a token_id
+pub const LAST_TOKEN_ID: Item<u64> = Item::new("last_token_id");
+pub const MODEL_SUPPLY: Map<&str, Supply> = Map::new("model_supply");
+/// Stored as (model_id, size)
+pub const SIZE_SUPPLY: Map<(&str, &str), Supply> = Map::new("size_supply");
+pub const ROYALTY_TIMELOCK: Item<RoyaltyTimelock> = Item::new("royalty_timelock");
+/// Collection royalty increase waiting for its announcement period to pass
+pub const PENDING_ROYALTY: Item<PendingRoyalty> = Item::new("pending_royalty");
+pub const ROYALTY_INCREASE_WINDOW: Item<RoyaltyIncreaseWindow> =
+    Item::new("royalty_increase_window");
+/// Address confirming that physical pairs were shipped
+pub const REDEEMER: Item<Addr> = Item::new("redeemer");
+/// Redemptions by token_id, kept after the token is burned
+pub const REDEMPTIONS: Map<&str, Redemption> = Map::new("redemptions");
+/// Tokens locked by the collection owner, e.g. reported stolen or counterfeit
+pub const FROZEN_TOKENS: Map<&str, FrozenToken> = Map::new("frozen_tokens");
+/// Controls collection info, collection royalties and metadata freezing
+pub const COLLECTION_OWNER: Item<Addr> = Item::new("collection_owner");
+pub const PENDING_COLLECTION_OWNER: Item<Addr> = Item::new("pending_collection_owner");
+/// Once set, token and model uris can no longer be changed
+pub const METADATA_FROZEN: Item<bool> = Item::new("metadata_frozen");
+/// Stored as (trait_type, value, token_id)
+pub const TOKEN_TRAITS: Map<(&str, &str, &str), Empty> = Map::new("token_traits");
+/// Stored as (trait_type, value, model_id)
+pub const MODEL_TRAITS: Map<(&str, &str, &str), Empty> = Map::new("model_traits");
+
+/// Collection royalty in force at the given block, counting a pending increase whose
+/// announcement period has passed
+pub fn collection_royalty(storage: &dyn Storage, block: &BlockInfo) -> StdResult<Option<RoyaltyInfo>> {
+    match PENDING_ROYALTY.may_load(storage)? {
+        Some(pending) if block.time >= pending.effective_at => Ok(pending.royalty_info),
+        _ => Ok(COLLECTION_INFO.load(storage)?.royalty_info),
+    }
+}
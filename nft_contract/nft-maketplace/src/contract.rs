@@ -1,12 +1,13 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{to_binary, Binary, Decimal, Deps, DepsMut, Env, MessageInfo, Order, Response, StdError, StdResult, Storage};
 use cw2::set_contract_version;
 
 use crate::error::ContractError;
-use crate::execute::{exec_add_nft_contract, exec_withdraw_nft, exec_make_offer, exec_bid, exec_close_bid, exec_update_price, exec_receive_nft};
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::{ContractInfo, CONTRACT_INFO, ADMIN, NFT_CONTRACTS};
+use crate::execute::{exec_add_nft_contract, exec_remove_nft_contract, exec_set_nft_contract_suspended, exec_update_collection_config, exec_add_code_id, exec_remove_code_id, validate_fee, exec_withdraw_nft, exec_make_offer, exec_bid, exec_close_bid, exec_update_price, exec_receive_nft, exec_set_paused, exec_update_config, exec_propose_admin, exec_accept_admin};
+use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
+use crate::query::{query_auctions, query_code_ids, query_config, query_nft_contract, query_nft_contracts, query_offering, query_offerings};
+use crate::state::{ContractInfo, CollectionConfig, RoyaltyPolicy, CONTRACT_INFO, LEGACY_CONTRACT_INFO, LEGACY_NFT_CONTRACTS, LEGACY_OFFERINGS, ADMIN, NFT_CONTRACTS, OFFERINGS};
 
 
 const CONTRACT_NAME: &str = "crates.io:maketplace";
@@ -22,17 +23,16 @@ pub fn instantiate(
     let admin = msg.admin.unwrap_or_default();
     let validate_admin = deps.api.addr_validate(&admin).unwrap_or(info.sender);
     ADMIN.save(deps.storage, &validate_admin)?;
-    let info = ContractInfo { name: msg.name, native_denom: msg.native_denom};
+    let fee = msg.fee.unwrap_or_default();
+    validate_fee(fee)?;
+    let royalty_policy = msg.royalty_policy.unwrap_or(RoyaltyPolicy::Snapshot);
+    let info = ContractInfo { name: msg.name, native_denom: msg.native_denom, fee, royalty_policy };
     CONTRACT_INFO.save(deps.storage, &info)?;
 
-    let mut nft_contracts= vec![];
     for address in &msg.nft_contracts {
         let nft_contract = deps.api.addr_validate(&address)?;
-        if !nft_contracts.contains(&nft_contract) {
-            nft_contracts.push(nft_contract);
-        }
+        NFT_CONTRACTS.save(deps.storage, &nft_contract, &CollectionConfig::default())?;
     }
-    NFT_CONTRACTS.save(deps.storage, &nft_contracts)?;
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     Ok(Response::new()
         .add_attribute("action", "instantiate")
@@ -50,20 +50,815 @@ pub fn execute(
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::AddNFTContract { address } => exec_add_nft_contract(deps, env, info, address), 
+        ExecuteMsg::AddNFTContract { address, config } => exec_add_nft_contract(deps, env, info, address, config),
+        ExecuteMsg::UpdateCollectionConfig { address, config } => exec_update_collection_config(deps, env, info, address, config),
+        ExecuteMsg::RemoveNFTContract { address } => exec_remove_nft_contract(deps, env, info, address),
+        ExecuteMsg::SuspendNFTContract { address } => exec_set_nft_contract_suspended(deps, env, info, address, true),
+        ExecuteMsg::ResumeNFTContract { address } => exec_set_nft_contract_suspended(deps, env, info, address, false),
+        ExecuteMsg::AddCodeId { code_id } => exec_add_code_id(deps, env, info, code_id),
+        ExecuteMsg::RemoveCodeId { code_id } => exec_remove_code_id(deps, env, info, code_id),
         ExecuteMsg::WithdrawNft { offering_id } => exec_withdraw_nft(deps, env, info, offering_id),
         ExecuteMsg::MakeOffer { offering_id } => exec_make_offer(deps, env, info, offering_id),
         ExecuteMsg::Bid { offering_id } => exec_bid(deps, env, info, offering_id),
         ExecuteMsg::CloseBid { offering_id } => exec_close_bid(deps, env, info, offering_id),
         ExecuteMsg::UpdatePrice { offering_id, update_price } => exec_update_price(deps, env, info, offering_id, update_price),
-        ExecuteMsg::ReceiveNft(msg) => exec_receive_nft(deps, env, info, msg)
+        ExecuteMsg::ReceiveNft(msg) => exec_receive_nft(deps, env, info, msg),
+        ExecuteMsg::Pause {} => exec_set_paused(deps, env, info, true),
+        ExecuteMsg::Unpause {} => exec_set_paused(deps, env, info, false),
+        ExecuteMsg::UpdateConfig { name, native_denom, fee, royalty_policy } => exec_update_config(deps, env, info, name, native_denom, fee, royalty_policy),
+        ExecuteMsg::ProposeAdmin { address } => exec_propose_admin(deps, env, info, address),
+        ExecuteMsg::AcceptAdmin {} => exec_accept_admin(deps, env, info),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(_deps: Deps, _env: Env, _msg: QueryMsg) -> StdResult<Binary> {
-    unimplemented!()
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::Config {} => to_binary(&query_config(deps)?),
+        QueryMsg::NftContract { address } => to_binary(&query_nft_contract(deps, address)?),
+        QueryMsg::NftContracts { start_after, limit } => to_binary(&query_nft_contracts(deps, start_after, limit)?),
+        QueryMsg::CodeIds { start_after, limit } => to_binary(&query_code_ids(deps, start_after, limit)?),
+        QueryMsg::Offering { offering_id } => to_binary(&query_offering(deps, offering_id)?),
+        QueryMsg::Offerings { start_after, limit } => to_binary(&query_offerings(deps, start_after, limit)?),
+        QueryMsg::Auctions { status, start_after, limit } => to_binary(&query_auctions(deps, env, status, start_after, limit)?),
+    }
+}
+
+/// Moves the config off the cw2 key, carries over the old whitelist, converts single-recipient
+/// royalties of open listings and prices listings created before denoms in the native denom
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let contract_info = match CONTRACT_INFO.may_load(deps.storage)? {
+        Some(contract_info) => contract_info,
+        None => load_legacy_contract_info(deps.storage, msg)?,
+    };
+    CONTRACT_INFO.save(deps.storage, &contract_info)?;
+
+    if let Some(nft_contracts) = LEGACY_NFT_CONTRACTS.may_load(deps.storage)? {
+        for nft_contract in nft_contracts {
+            if !NFT_CONTRACTS.has(deps.storage, &nft_contract) {
+                NFT_CONTRACTS.save(deps.storage, &nft_contract, &CollectionConfig::default())?;
+            }
+        }
+        LEGACY_NFT_CONTRACTS.remove(deps.storage);
+    }
+
+    let native_denom = contract_info.native_denom;
+    // raw keys, `keys` would decode the not yet converted values
+    let ids = OFFERINGS
+        .keys_raw(deps.storage, None, None, Order::Ascending)
+        .map(|key| String::from_utf8(key).map_err(StdError::from))
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let mut migrated = 0u64;
+    for id in ids {
+        let (mut offer, mut changed) = match OFFERINGS.load(deps.storage, &id) {
+            Ok(offer) => (offer, false),
+            Err(_) => (LEGACY_OFFERINGS.load(deps.storage, &id)?.into(), true),
+        };
+        if offer.denom.is_empty() {
+            offer.denom = native_denom.clone();
+            changed = true;
+        }
+        if changed {
+            OFFERINGS.save(deps.storage, &id, &offer)?;
+            migrated += 1;
+        }
+    }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "migrate")
+        .add_attribute("migrated_offerings", migrated.to_string()))
+}
+
+fn load_legacy_contract_info(storage: &dyn Storage, msg: MigrateMsg) -> Result<ContractInfo, ContractError> {
+    if let Ok(contract_info) = LEGACY_CONTRACT_INFO.load(storage) {
+        return Ok(contract_info);
+    }
+    match (msg.name, msg.native_denom) {
+        (Some(name), Some(native_denom)) => Ok(ContractInfo {
+            name,
+            native_denom,
+            fee: Decimal::zero(),
+            royalty_policy: RoyaltyPolicy::default(),
+        }),
+        _ => Err(ContractError::MissingContractInfo {}),
+    }
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage};
+    use cosmwasm_std::{coins, from_binary, Addr, BankMsg, ContractInfoResponse as WasmContractInfoResponse, ContractResult, CosmosMsg, Decimal, OwnedDeps, SystemError, SystemResult, Timestamp, Uint128, WasmMsg, WasmQuery};
+    use cw721::{Cw721ExecuteMsg, Cw721ReceiveMsg, Expiration};
+    use nft_base::msg::{Cw2981QueryMsg, RoyaltiesInfoResponse, RoyaltyPaymentsResponse, RoyaltyRecipientResponse, TokenStatusResponse};
+    use nft_base::QueryMsg as NFTQueryMsg;
+
+    use crate::execute::parse_list_nft_msg;
+    use crate::msg::ListNftMsg;
+    use crate::package::{AuctionsResponse, ContractInfoResponse, NftContractsResponse};
+    use crate::state::{AuctionStatus, Bid, SaleType, BID_OFFERINGS};
+
+    const ADMIN_ADDR: &str = "admin";
+    const NFT_ADDR: &str = "nft_contract";
+    const SELLER: &str = "seller";
+    const BUYER: &str = "buyer";
+    const DESIGNER: &str = "designer";
+    const BIDDER_ONE: &str = "bidder_one";
+    const BIDDER_TWO: &str = "bidder_two";
+    const DENOM: &str = "uaura";
+    const TRUSTED_CODE_ID: u64 = 7;
+
+    fn setup(royalty_policy: Option<RoyaltyPolicy>) -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+        let mut deps = mock_dependencies();
+        let msg = InstantiateMsg {
+            admin: Some(ADMIN_ADDR.to_string()),
+            name: "marketplace".to_string(),
+            native_denom: DENOM.to_string(),
+            fee: Some(Decimal::percent(2)),
+            royalty_policy,
+            nft_contracts: vec![NFT_ADDR.to_string()],
+        };
+        instantiate(deps.as_mut(), mock_env(), mock_info(ADMIN_ADDR, &[]), msg).unwrap();
+        deps
+    }
+
+    /// Answers the nft-base queries of every collection, paying `royalty_share` to the designer.
+    /// All collections are instantiated from `TRUSTED_CODE_ID`
+    fn mock_collection(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>, royalty_share: Decimal) {
+        deps.querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { msg, .. } => {
+                let res = match from_binary(msg).unwrap() {
+                    NFTQueryMsg::RoyaltyPayments { sale_price, .. } => to_binary(&RoyaltyPaymentsResponse {
+                        payments: vec![RoyaltiesInfoResponse {
+                            address: DESIGNER.to_string(),
+                            royalty_amount: sale_price * royalty_share,
+                        }],
+                    }),
+                    NFTQueryMsg::TokenStatus { .. } => to_binary(&TokenStatusResponse {
+                        transferable: true,
+                        redemption: None,
+                    }),
+                    _ => return unknown_variant(),
+                };
+                SystemResult::Ok(ContractResult::Ok(res.unwrap()))
+            }
+            WasmQuery::ContractInfo { .. } => {
+                let res = to_binary(&WasmContractInfoResponse::new(TRUSTED_CODE_ID, "creator"));
+                SystemResult::Ok(ContractResult::Ok(res.unwrap()))
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest { kind: "wasm".to_string() }),
+        });
+    }
+
+    /// Same as `mock_collection`, but for a plain CW2981 collection without `RoyaltyPayments`
+    fn mock_cw2981_collection(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>, royalty_share: Decimal) {
+        deps.querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { msg, .. } => {
+                let res = match from_binary(msg).unwrap() {
+                    NFTQueryMsg::Extension { msg: Cw2981QueryMsg::RoyaltyInfo { sale_price, .. } } => {
+                        to_binary(&RoyaltiesInfoResponse {
+                            address: DESIGNER.to_string(),
+                            royalty_amount: sale_price * royalty_share,
+                        })
+                    }
+                    _ => return unknown_variant(),
+                };
+                SystemResult::Ok(ContractResult::Ok(res.unwrap()))
+            }
+            WasmQuery::ContractInfo { .. } => {
+                let res = to_binary(&WasmContractInfoResponse::new(TRUSTED_CODE_ID, "creator"));
+                SystemResult::Ok(ContractResult::Ok(res.unwrap()))
+            }
+            _ => SystemResult::Err(SystemError::UnsupportedRequest { kind: "wasm".to_string() }),
+        });
+    }
+
+    /// The error a contract returns for a query variant it does not know
+    fn unknown_variant() -> SystemResult<ContractResult<Binary>> {
+        let err = "Error parsing into type cw721_base::msg::QueryMsg: unknown variant `royalty_payments`";
+        SystemResult::Ok(ContractResult::Err(err.to_string()))
+    }
+
+    fn list_from(
+        deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        nft_address: &str,
+        sale_type: SaleType,
+    ) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+            sender: SELLER.to_string(),
+            token_id: "1".to_string(),
+            msg: to_binary(&ListNftMsg { sale_type, denom: None }).unwrap(),
+        });
+        execute(deps.as_mut(), mock_env(), mock_info(nft_address, &[]), msg)
+    }
+
+    fn list(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>, sale_type: SaleType) -> String {
+        list_from(deps, NFT_ADDR, sale_type).unwrap();
+        crate::state::num_offerings(&deps.storage).unwrap().to_string()
+    }
+
+    fn auction(start_time: Option<Timestamp>) -> SaleType {
+        SaleType::Auction(Bid {
+            start_price: Uint128::new(100),
+            increase_per_bid: Some(Uint128::new(10)),
+            expiration: Expiration::AtTime(mock_env().block.time.plus_seconds(1000)),
+            buy_now_price: Some(Uint128::new(1000)),
+            start_time,
+        })
+    }
+
+    fn bid(
+        deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>,
+        env: Env,
+        bidder: &str,
+        amount: u128,
+        offering_id: &str,
+    ) -> Result<Response, ContractError> {
+        let info = mock_info(bidder, &coins(amount, DENOM));
+        execute(deps.as_mut(), env, info, ExecuteMsg::Bid { offering_id: offering_id.to_string() })
+    }
+
+    fn paid_to(res: &Response, address: &str) -> Uint128 {
+        res.messages
+            .iter()
+            .filter_map(|sub| match &sub.msg {
+                CosmosMsg::Bank(BankMsg::Send { to_address, amount }) if to_address == address => {
+                    Some(amount[0].amount)
+                }
+                _ => None,
+            })
+            .sum()
+    }
+
+    fn nft_recipient(res: &Response) -> Option<String> {
+        res.messages.iter().find_map(|sub| match &sub.msg {
+            CosmosMsg::Wasm(WasmMsg::Execute { msg, .. }) => match from_binary(msg).unwrap() {
+                Cw721ExecuteMsg::TransferNft { recipient, .. } => Some(recipient),
+                _ => None,
+            },
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn list_nft_msg_accepts_plain_sale_type() {
+        let msg = br#"{"sale_type":{"FixedPrice":"100"},"denom":"uatom"}"#;
+        let parsed = parse_list_nft_msg(&Binary::from(msg.as_ref())).unwrap();
+        assert_eq!(parsed.sale_type, SaleType::FixedPrice(Uint128::new(100)));
+        assert_eq!(parsed.denom, Some("uatom".to_string()));
+
+        let msg = br#"{"FixedPrice":"100"}"#;
+        let parsed = parse_list_nft_msg(&Binary::from(msg.as_ref())).unwrap();
+        assert_eq!(parsed.sale_type, SaleType::FixedPrice(Uint128::new(100)));
+        assert_eq!(parsed.denom, None);
+
+        let msg = br#"{"Auction":{"start_price":"100","expiration":{"at_height":12345}}}"#;
+        let parsed = parse_list_nft_msg(&Binary::from(msg.as_ref())).unwrap();
+        match parsed.sale_type {
+            SaleType::Auction(bid) => {
+                assert_eq!(bid.start_price, Uint128::new(100));
+                assert_eq!(bid.expiration, Expiration::AtHeight(12345));
+                assert_eq!(bid.buy_now_price, None);
+            }
+            _ => panic!("expected an auction"),
+        }
+
+        let msg = br#"{"Swap":"100"}"#;
+        parse_list_nft_msg(&Binary::from(msg.as_ref())).unwrap_err();
+    }
+
+    #[test]
+    fn snapshot_policy_pays_royalty_from_listing() {
+        let mut deps = setup(None);
+        mock_collection(&mut deps, Decimal::percent(5));
+        let offering_id = list(&mut deps, SaleType::FixedPrice(Uint128::new(1000)));
+
+        // the creator raises the royalty after the listing
+        mock_collection(&mut deps, Decimal::percent(10));
+        let info = mock_info(BUYER, &coins(1000, DENOM));
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::MakeOffer { offering_id }).unwrap();
+
+        assert_eq!(paid_to(&res, DESIGNER), Uint128::new(50));
+        assert_eq!(paid_to(&res, ADMIN_ADDR), Uint128::new(20));
+        assert_eq!(paid_to(&res, SELLER), Uint128::new(930));
+        assert_eq!(nft_recipient(&res), Some(BUYER.to_string()));
+    }
+
+    #[test]
+    fn live_policy_pays_royalty_at_settlement() {
+        let mut deps = setup(Some(RoyaltyPolicy::Live));
+        mock_collection(&mut deps, Decimal::percent(5));
+        let offering_id = list(&mut deps, SaleType::FixedPrice(Uint128::new(1000)));
+
+        mock_collection(&mut deps, Decimal::percent(10));
+        let info = mock_info(BUYER, &coins(1000, DENOM));
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::MakeOffer { offering_id }).unwrap();
+
+        assert_eq!(paid_to(&res, DESIGNER), Uint128::new(100));
+        assert_eq!(paid_to(&res, ADMIN_ADDR), Uint128::new(20));
+        assert_eq!(paid_to(&res, SELLER), Uint128::new(880));
+    }
+
+    #[test]
+    fn royalty_is_split_across_recipients() {
+        let mut deps = setup(None);
+        let co_designer = "co_designer";
+        deps.querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { msg, .. } => match from_binary(msg).unwrap() {
+                NFTQueryMsg::RoyaltyPayments { sale_price, .. } => {
+                    let res = to_binary(&RoyaltyPaymentsResponse {
+                        payments: vec![
+                            RoyaltiesInfoResponse { address: DESIGNER.to_string(), royalty_amount: sale_price * Decimal::percent(3) },
+                            RoyaltiesInfoResponse { address: co_designer.to_string(), royalty_amount: sale_price * Decimal::percent(2) },
+                        ],
+                    });
+                    SystemResult::Ok(ContractResult::Ok(res.unwrap()))
+                }
+                _ => unknown_variant(),
+            },
+            _ => SystemResult::Err(SystemError::UnsupportedRequest { kind: "wasm".to_string() }),
+        });
+        let offering_id = list(&mut deps, SaleType::FixedPrice(Uint128::new(1000)));
+
+        let offer = OFFERINGS.load(&deps.storage, &offering_id).unwrap();
+        assert_eq!(offer.royalty_info.unwrap().recipients, vec![
+            RoyaltyRecipientResponse { payment_address: DESIGNER.to_string(), share: Decimal::percent(3) },
+            RoyaltyRecipientResponse { payment_address: co_designer.to_string(), share: Decimal::percent(2) },
+        ]);
+
+        let info = mock_info(BUYER, &coins(1000, DENOM));
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::MakeOffer { offering_id }).unwrap();
+        assert_eq!(paid_to(&res, DESIGNER), Uint128::new(30));
+        assert_eq!(paid_to(&res, co_designer), Uint128::new(20));
+        assert_eq!(paid_to(&res, ADMIN_ADDR), Uint128::new(20));
+        assert_eq!(paid_to(&res, SELLER), Uint128::new(930));
+    }
+
+    #[test]
+    fn cw2981_collection_pays_standard_royalty() {
+        let mut deps = setup(Some(RoyaltyPolicy::Live));
+        mock_cw2981_collection(&mut deps, Decimal::percent(5));
+        let offering_id = list(&mut deps, SaleType::FixedPrice(Uint128::new(1000)));
+
+        let info = mock_info(BUYER, &coins(1000, DENOM));
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::MakeOffer { offering_id }).unwrap();
+
+        assert_eq!(paid_to(&res, DESIGNER), Uint128::new(50));
+        assert_eq!(paid_to(&res, SELLER), Uint128::new(930));
+    }
+
+    #[test]
+    fn failing_royalty_query_is_returned() {
+        let mut deps = setup(Some(RoyaltyPolicy::Live));
+        mock_collection(&mut deps, Decimal::percent(5));
+        let offering_id = list(&mut deps, SaleType::FixedPrice(Uint128::new(1000)));
+
+        deps.querier.update_wasm(|_| SystemResult::Ok(ContractResult::Err("out of gas".to_string())));
+        let info = mock_info(BUYER, &coins(1000, DENOM));
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::MakeOffer { offering_id }).unwrap_err();
+        assert!(matches!(err, ContractError::Std(_)));
+    }
+
+    #[test]
+    fn royalty_above_price_is_rejected() {
+        let mut deps = setup(Some(RoyaltyPolicy::Live));
+        mock_collection(&mut deps, Decimal::percent(99));
+        let offering_id = list(&mut deps, SaleType::FixedPrice(Uint128::new(1000)));
+
+        let info = mock_info(BUYER, &coins(1000, DENOM));
+        let err = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::MakeOffer { offering_id }).unwrap_err();
+        assert!(matches!(err, ContractError::RoyaltyExceedsPrice {}));
+    }
+
+    #[test]
+    fn switching_policy_applies_to_open_listings() {
+        let mut deps = setup(None);
+        mock_collection(&mut deps, Decimal::percent(5));
+        let offering_id = list(&mut deps, SaleType::FixedPrice(Uint128::new(1000)));
+
+        mock_collection(&mut deps, Decimal::zero());
+        let msg = ExecuteMsg::UpdateConfig {
+            name: None,
+            native_denom: None,
+            fee: None,
+            royalty_policy: Some(RoyaltyPolicy::Live),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info(ADMIN_ADDR, &[]), msg).unwrap();
+
+        let info = mock_info(BUYER, &coins(1000, DENOM));
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::MakeOffer { offering_id }).unwrap();
+        assert_eq!(paid_to(&res, DESIGNER), Uint128::zero());
+        assert_eq!(paid_to(&res, SELLER), Uint128::new(980));
+    }
+
+    #[test]
+    fn outbid_bidder_is_refunded() {
+        let mut deps = setup(None);
+        mock_collection(&mut deps, Decimal::zero());
+        let offering_id = list(&mut deps, auction(None));
+
+        let err = bid(&mut deps, mock_env(), BIDDER_ONE, 99, &offering_id).unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientDeposit {}));
+        let res = bid(&mut deps, mock_env(), BIDDER_ONE, 100, &offering_id).unwrap();
+        assert!(res.messages.is_empty());
+
+        // a new bid must beat the highest one by more than the increment
+        let err = bid(&mut deps, mock_env(), BIDDER_TWO, 110, &offering_id).unwrap_err();
+        assert!(matches!(err, ContractError::InsufficientDeposit {}));
+        let res = bid(&mut deps, mock_env(), BIDDER_TWO, 150, &offering_id).unwrap();
+        assert_eq!(paid_to(&res, BIDDER_ONE), Uint128::new(100));
+
+        let bid_offering = BID_OFFERINGS.load(&deps.storage, &offering_id).unwrap();
+        assert_eq!(bid_offering.highest_bid_price, Some(Uint128::new(150)));
+        assert_eq!(bid_offering.address, Some(Addr::unchecked(BIDDER_TWO)));
+    }
+
+    #[test]
+    fn buy_now_ends_auction() {
+        let mut deps = setup(None);
+        mock_collection(&mut deps, Decimal::zero());
+        let offering_id = list(&mut deps, auction(None));
+        bid(&mut deps, mock_env(), BIDDER_ONE, 100, &offering_id).unwrap();
+
+        let res = bid(&mut deps, mock_env(), BUYER, 1000, &offering_id).unwrap();
+        assert_eq!(res.attributes[0].value, "buy_now");
+        assert_eq!(paid_to(&res, BIDDER_ONE), Uint128::new(100));
+        assert_eq!(paid_to(&res, ADMIN_ADDR), Uint128::new(20));
+        assert_eq!(paid_to(&res, SELLER), Uint128::new(980));
+        assert_eq!(nft_recipient(&res), Some(BUYER.to_string()));
+        assert!(!OFFERINGS.has(&deps.storage, &offering_id));
+        assert!(!BID_OFFERINGS.has(&deps.storage, &offering_id));
+    }
+
+    #[test]
+    fn seller_close_refunds_highest_bidder() {
+        let mut deps = setup(None);
+        mock_collection(&mut deps, Decimal::zero());
+        let offering_id = list(&mut deps, auction(None));
+        bid(&mut deps, mock_env(), BIDDER_ONE, 100, &offering_id).unwrap();
+
+        let msg = ExecuteMsg::CloseBid { offering_id: offering_id.clone() };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(BIDDER_TWO, &[]), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        let res = execute(deps.as_mut(), mock_env(), mock_info(SELLER, &[]), msg).unwrap();
+        assert_eq!(paid_to(&res, BIDDER_ONE), Uint128::new(100));
+        assert_eq!(nft_recipient(&res), Some(SELLER.to_string()));
+        assert!(!OFFERINGS.has(&deps.storage, &offering_id));
+        assert!(!BID_OFFERINGS.has(&deps.storage, &offering_id));
+    }
+
+    #[test]
+    fn winner_closes_expired_auction() {
+        let mut deps = setup(None);
+        mock_collection(&mut deps, Decimal::zero());
+        let offering_id = list(&mut deps, auction(None));
+        bid(&mut deps, mock_env(), BIDDER_ONE, 100, &offering_id).unwrap();
+
+        let msg = ExecuteMsg::CloseBid { offering_id: offering_id.clone() };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(BIDDER_ONE, &[]), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(1000);
+        let err = bid(&mut deps, env.clone(), BIDDER_TWO, 200, &offering_id).unwrap_err();
+        assert!(matches!(err, ContractError::BidExpiration {}));
+
+        let res = execute(deps.as_mut(), env, mock_info(BIDDER_ONE, &[]), msg).unwrap();
+        assert_eq!(paid_to(&res, ADMIN_ADDR), Uint128::new(2));
+        assert_eq!(paid_to(&res, SELLER), Uint128::new(98));
+        assert_eq!(nft_recipient(&res), Some(BIDDER_ONE.to_string()));
+        assert!(!OFFERINGS.has(&deps.storage, &offering_id));
+        assert!(!BID_OFFERINGS.has(&deps.storage, &offering_id));
+    }
+
+    #[test]
+    fn bids_wait_for_start_time() {
+        let mut deps = setup(None);
+        mock_collection(&mut deps, Decimal::zero());
+        let start_time = mock_env().block.time.plus_seconds(100);
+        let offering_id = list(&mut deps, auction(Some(start_time)));
+
+        let err = bid(&mut deps, mock_env(), BIDDER_ONE, 100, &offering_id).unwrap_err();
+        assert!(matches!(err, ContractError::AuctionNotStarted {}));
+
+        let mut env = mock_env();
+        env.block.time = start_time;
+        bid(&mut deps, env, BIDDER_ONE, 100, &offering_id).unwrap();
+    }
+
+    #[test]
+    fn auctions_query_scans_at_most_limit() {
+        let mut deps = setup(None);
+        mock_collection(&mut deps, Decimal::zero());
+        let upcoming = auction(Some(mock_env().block.time.plus_seconds(100)));
+        list(&mut deps, upcoming.clone());
+        list(&mut deps, SaleType::FixedPrice(Uint128::new(1000)));
+        list(&mut deps, auction(None));
+        list(&mut deps, upcoming);
+
+        let query_live = |deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>, start_after: Option<String>| {
+            let msg = QueryMsg::Auctions { status: Some(AuctionStatus::Live), start_after, limit: Some(2) };
+            from_binary::<AuctionsResponse>(&query(deps.as_ref(), mock_env(), msg).unwrap()).unwrap()
+        };
+        let page = query_live(&deps, None);
+        let ids: Vec<_> = page.auctions.iter().map(|auction| auction.id.as_str()).collect();
+        assert_eq!(ids, vec!["3"]);
+        assert_eq!(page.last_scanned, Some("3".to_string()));
+
+        let page = query_live(&deps, page.last_scanned);
+        assert!(page.auctions.is_empty());
+        assert_eq!(page.last_scanned, None);
+    }
+
+    #[test]
+    fn auction_must_start_before_expiration() {
+        let mut deps = setup(None);
+        mock_collection(&mut deps, Decimal::zero());
+        let sale_type = auction(Some(mock_env().block.time.plus_seconds(1000)));
+        let err = list_from(&mut deps, NFT_ADDR, sale_type).unwrap_err();
+        assert!(matches!(err, ContractError::StartTimeAfterExpiration {}));
+    }
+
+    #[test]
+    fn removed_collection_stays_removed() {
+        let mut deps = setup(None);
+        mock_collection(&mut deps, Decimal::zero());
+        let trusted_nft = "trusted_nft";
+        let sale_type = SaleType::FixedPrice(Uint128::new(1000));
+
+        let err = list_from(&mut deps, trusted_nft, sale_type.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::NFTAddressNotMatch {}));
+
+        let msg = ExecuteMsg::AddCodeId { code_id: TRUSTED_CODE_ID };
+        execute(deps.as_mut(), mock_env(), mock_info(ADMIN_ADDR, &[]), msg).unwrap();
+        list_from(&mut deps, trusted_nft, sale_type.clone()).unwrap();
+
+        let msg = ExecuteMsg::RemoveNFTContract { address: trusted_nft.to_string() };
+        execute(deps.as_mut(), mock_env(), mock_info(ADMIN_ADDR, &[]), msg).unwrap();
+        let err = list_from(&mut deps, trusted_nft, sale_type.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::NFTAddressNotMatch {}));
+
+        // only the admin can bring it back
+        let msg = ExecuteMsg::AddNFTContract { address: trusted_nft.to_string(), config: None };
+        execute(deps.as_mut(), mock_env(), mock_info(ADMIN_ADDR, &[]), msg).unwrap();
+        list_from(&mut deps, trusted_nft, sale_type).unwrap();
+    }
+
+    fn config(deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>) -> ContractInfoResponse {
+        from_binary(&query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn paused_marketplace_stops_trading() {
+        let mut deps = setup(None);
+        mock_collection(&mut deps, Decimal::zero());
+        let offering_id = list(&mut deps, SaleType::FixedPrice(Uint128::new(1000)));
+        let withdrawn_id = list(&mut deps, SaleType::FixedPrice(Uint128::new(1000)));
+
+        let err = execute(deps.as_mut(), mock_env(), mock_info(SELLER, &[]), ExecuteMsg::Pause {}).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), mock_env(), mock_info(ADMIN_ADDR, &[]), ExecuteMsg::Pause {}).unwrap();
+        assert!(config(&deps).paused);
+
+        let err = list_from(&mut deps, NFT_ADDR, SaleType::FixedPrice(Uint128::new(1000))).unwrap_err();
+        assert!(matches!(err, ContractError::Paused {}));
+        let info = mock_info(BUYER, &coins(1000, DENOM));
+        let msg = ExecuteMsg::MakeOffer { offering_id: offering_id.clone() };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Paused {}));
+        // sellers can still take their NFTs back
+        let withdraw = ExecuteMsg::WithdrawNft { offering_id: withdrawn_id };
+        execute(deps.as_mut(), mock_env(), mock_info(SELLER, &[]), withdraw).unwrap();
+
+        execute(deps.as_mut(), mock_env(), mock_info(ADMIN_ADDR, &[]), ExecuteMsg::Unpause {}).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(nft_recipient(&res), Some(BUYER.to_string()));
+    }
+
+    #[test]
+    fn admin_handover_needs_acceptance() {
+        let mut deps = setup(None);
+        let new_admin = "new_admin";
+
+        let err = execute(deps.as_mut(), mock_env(), mock_info(new_admin, &[]), ExecuteMsg::AcceptAdmin {}).unwrap_err();
+        assert!(matches!(err, ContractError::NoPendingAdmin {}));
+        let msg = ExecuteMsg::ProposeAdmin { address: new_admin.to_string() };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(new_admin, &[]), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), mock_env(), mock_info(ADMIN_ADDR, &[]), msg).unwrap();
+        assert_eq!(config(&deps).pending_admin, Some(new_admin.to_string()));
+
+        let err = execute(deps.as_mut(), mock_env(), mock_info(SELLER, &[]), ExecuteMsg::AcceptAdmin {}).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), mock_env(), mock_info(new_admin, &[]), ExecuteMsg::AcceptAdmin {}).unwrap();
+        let contract_info = config(&deps);
+        assert_eq!(contract_info.admin, new_admin);
+        assert_eq!(contract_info.pending_admin, None);
+
+        let update_fee = |fee| ExecuteMsg::UpdateConfig { name: None, native_denom: None, fee: Some(fee), royalty_policy: None };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(ADMIN_ADDR, &[]), update_fee(Decimal::percent(3))).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        let err = execute(deps.as_mut(), mock_env(), mock_info(new_admin, &[]), update_fee(Decimal::percent(11))).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidFee {}));
+        execute(deps.as_mut(), mock_env(), mock_info(new_admin, &[]), update_fee(Decimal::percent(3))).unwrap();
+        assert_eq!(config(&deps).fee, Decimal::percent(3));
+    }
+
+    #[test]
+    fn suspended_collection_cannot_trade() {
+        let mut deps = setup(None);
+        mock_collection(&mut deps, Decimal::zero());
+        let offering_id = list(&mut deps, SaleType::FixedPrice(Uint128::new(1000)));
+        let withdrawn_id = list(&mut deps, SaleType::FixedPrice(Uint128::new(1000)));
+
+        let suspend = ExecuteMsg::SuspendNFTContract { address: NFT_ADDR.to_string() };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(SELLER, &[]), suspend.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), mock_env(), mock_info(ADMIN_ADDR, &[]), suspend).unwrap();
+
+        let err = list_from(&mut deps, NFT_ADDR, SaleType::FixedPrice(Uint128::new(1000))).unwrap_err();
+        assert!(matches!(err, ContractError::CollectionSuspended {}));
+        let info = mock_info(BUYER, &coins(1000, DENOM));
+        let msg = ExecuteMsg::MakeOffer { offering_id };
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::CollectionSuspended {}));
+        let withdraw = ExecuteMsg::WithdrawNft { offering_id: withdrawn_id };
+        execute(deps.as_mut(), mock_env(), mock_info(SELLER, &[]), withdraw).unwrap();
+
+        // neither a config update nor re-adding the collection resumes it
+        let config = CollectionConfig { fee: Some(Decimal::percent(1)), ..CollectionConfig::default() };
+        let msg_update = ExecuteMsg::UpdateCollectionConfig { address: NFT_ADDR.to_string(), config: config.clone() };
+        execute(deps.as_mut(), mock_env(), mock_info(ADMIN_ADDR, &[]), msg_update).unwrap();
+        let msg_add = ExecuteMsg::AddNFTContract { address: NFT_ADDR.to_string(), config: Some(config) };
+        execute(deps.as_mut(), mock_env(), mock_info(ADMIN_ADDR, &[]), msg_add).unwrap();
+        let err = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::CollectionSuspended {}));
+
+        let resume = ExecuteMsg::ResumeNFTContract { address: NFT_ADDR.to_string() };
+        execute(deps.as_mut(), mock_env(), mock_info(ADMIN_ADDR, &[]), resume).unwrap();
+        let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+        assert_eq!(paid_to(&res, ADMIN_ADDR), Uint128::new(10));
+    }
+
+    #[test]
+    fn removed_collection_cannot_trade() {
+        let mut deps = setup(None);
+        mock_collection(&mut deps, Decimal::zero());
+        let offering_id = list(&mut deps, SaleType::FixedPrice(Uint128::new(1000)));
+
+        let remove = ExecuteMsg::RemoveNFTContract { address: NFT_ADDR.to_string() };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(SELLER, &[]), remove.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::Unauthorized {}));
+        execute(deps.as_mut(), mock_env(), mock_info(ADMIN_ADDR, &[]), remove).unwrap();
+
+        let query_msg = QueryMsg::NftContracts { start_after: None, limit: None };
+        let res: NftContractsResponse = from_binary(&query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert!(res.nft_contracts.is_empty());
+        let info = mock_info(BUYER, &coins(1000, DENOM));
+        let msg = ExecuteMsg::MakeOffer { offering_id: offering_id.clone() };
+        let err = execute(deps.as_mut(), mock_env(), info, msg).unwrap_err();
+        assert!(matches!(err, ContractError::NFTAddressNotMatch {}));
+        let withdraw = ExecuteMsg::WithdrawNft { offering_id };
+        execute(deps.as_mut(), mock_env(), mock_info(SELLER, &[]), withdraw).unwrap();
+    }
+
+    #[test]
+    fn collection_config_governs_listings() {
+        let mut deps = setup(None);
+        mock_collection(&mut deps, Decimal::percent(5));
+        let usdc = "uusdc";
+
+        let config = CollectionConfig {
+            fee: Some(Decimal::percent(11)),
+            allow_auction: false,
+            min_price: Some(Uint128::new(500)),
+            denoms: vec![DENOM.to_string(), usdc.to_string()],
+            enforce_royalties: false,
+            ..CollectionConfig::default()
+        };
+        let msg = ExecuteMsg::UpdateCollectionConfig { address: NFT_ADDR.to_string(), config: config.clone() };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(ADMIN_ADDR, &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::InvalidFee {}));
+        let config = CollectionConfig { fee: Some(Decimal::percent(5)), ..config };
+        let msg = ExecuteMsg::UpdateCollectionConfig { address: NFT_ADDR.to_string(), config };
+        execute(deps.as_mut(), mock_env(), mock_info(ADMIN_ADDR, &[]), msg).unwrap();
+
+        let err = list_from(&mut deps, NFT_ADDR, auction(None)).unwrap_err();
+        assert!(matches!(err, ContractError::SaleTypeNotAllowed {}));
+        let err = list_from(&mut deps, NFT_ADDR, SaleType::FixedPrice(Uint128::new(400))).unwrap_err();
+        assert!(matches!(err, ContractError::PriceBelowMinimum {}));
+
+        let list_in = |deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>, denom: &str| {
+            let msg = ExecuteMsg::ReceiveNft(Cw721ReceiveMsg {
+                sender: SELLER.to_string(),
+                token_id: "1".to_string(),
+                msg: to_binary(&ListNftMsg {
+                    sale_type: SaleType::FixedPrice(Uint128::new(1000)),
+                    denom: Some(denom.to_string()),
+                }).unwrap(),
+            });
+            execute(deps.as_mut(), mock_env(), mock_info(NFT_ADDR, &[]), msg)
+        };
+        let err = list_in(&mut deps, "uatom").unwrap_err();
+        assert!(matches!(err, ContractError::DenomNotMatch));
+        list_in(&mut deps, usdc).unwrap();
+        let offering_id = crate::state::num_offerings(&deps.storage).unwrap().to_string();
+        assert_eq!(OFFERINGS.load(&deps.storage, &offering_id).unwrap().denom, usdc);
+
+        let msg = ExecuteMsg::UpdatePrice { offering_id: offering_id.clone(), update_price: Uint128::new(400) };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(SELLER, &[]), msg).unwrap_err();
+        assert!(matches!(err, ContractError::PriceBelowMinimum {}));
+
+        let msg = ExecuteMsg::MakeOffer { offering_id };
+        let err = execute(deps.as_mut(), mock_env(), mock_info(BUYER, &coins(1000, DENOM)), msg.clone()).unwrap_err();
+        assert!(matches!(err, ContractError::DenomNotMatch));
+        let res = execute(deps.as_mut(), mock_env(), mock_info(BUYER, &coins(1000, usdc)), msg).unwrap();
+        // royalties are not enforced for this collection
+        assert_eq!(paid_to(&res, DESIGNER), Uint128::zero());
+        assert_eq!(paid_to(&res, ADMIN_ADDR), Uint128::new(50));
+        assert_eq!(paid_to(&res, SELLER), Uint128::new(950));
+    }
+
+    #[test]
+    fn migrate_from_baseline_state() {
+        let mut deps = mock_dependencies();
+        deps.storage.set(b"contract_info", br#"{"name":"marketplace","native_denom":"uaura"}"#);
+        deps.storage.set(b"admin", br#""admin""#);
+        deps.storage.set(b"nft_contracts", br#"["nft_contract","other_nft"]"#);
+        deps.storage.set(
+            &OFFERINGS.key("1"),
+            br#"{"token_id":"1","nft_address":"nft_contract","royalty_info":null,"seller":"seller","sale_type":{"FixedPrice":"1000"},"listing_time":"1571797419879305533"}"#,
+        );
+        deps.storage.set(
+            &OFFERINGS.key("2"),
+            br#"{"token_id":"2","nft_address":"nft_contract","royalty_info":{"payment_address":"designer","share":"0.05"},"seller":"seller","sale_type":{"Auction":{"start_price":"100","increase_per_bid":null,"expiration":{"at_height":99999999}}},"listing_time":"1571797419879305533"}"#,
+        );
+        deps.storage.set(&BID_OFFERINGS.key("2"), br#"{"highest_bid_price":null,"address":null,"start_timestamp":"1571797419879305533"}"#);
+
+        let msg = MigrateMsg { name: None, native_denom: None };
+        let res = migrate(deps.as_mut(), mock_env(), msg).unwrap();
+        assert_eq!(res.attributes[1].value, "2");
+
+        let contract_info = CONTRACT_INFO.load(&deps.storage).unwrap();
+        assert_eq!(contract_info.name, "marketplace");
+        assert_eq!(contract_info.native_denom, DENOM);
+        assert_eq!(contract_info.fee, Decimal::zero());
+        assert_eq!(contract_info.royalty_policy, RoyaltyPolicy::Snapshot);
+        assert_eq!(OFFERINGS.load(&deps.storage, "1").unwrap().denom, DENOM);
+        let offer = OFFERINGS.load(&deps.storage, "2").unwrap();
+        assert_eq!(offer.royalty_info.unwrap().recipients, vec![RoyaltyRecipientResponse {
+            payment_address: DESIGNER.to_string(),
+            share: Decimal::percent(5),
+        }]);
+        assert_eq!(cw2::get_contract_version(&deps.storage).unwrap().contract, CONTRACT_NAME);
+        let nft_contracts = NFT_CONTRACTS
+            .range(&deps.storage, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(nft_contracts, vec![
+            (Addr::unchecked(NFT_ADDR), CollectionConfig::default()),
+            (Addr::unchecked("other_nft"), CollectionConfig::default()),
+        ]);
+        assert!(deps.storage.get(b"nft_contracts").is_none());
+
+        // listings of whitelisted collections stay buyable
+        mock_collection(&mut deps, Decimal::zero());
+        let info = mock_info(BUYER, &coins(1000, DENOM));
+        let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::MakeOffer { offering_id: "1".to_string() }).unwrap();
+        assert_eq!(paid_to(&res, SELLER), Uint128::new(1000));
+        bid(&mut deps, mock_env(), BIDDER_ONE, 100, "2").unwrap();
+        let msg = ExecuteMsg::CloseBid { offering_id: "2".to_string() };
+        let res = execute(deps.as_mut(), mock_env(), mock_info(SELLER, &[]), msg).unwrap();
+        assert_eq!(paid_to(&res, BIDDER_ONE), Uint128::new(100));
+
+        // the config no longer shares its key with the contract version
+        let msg = MigrateMsg { name: None, native_denom: None };
+        let res = migrate(deps.as_mut(), mock_env(), msg).unwrap();
+        assert_eq!(res.attributes[1].value, "0");
+        assert_eq!(CONTRACT_INFO.load(&deps.storage).unwrap(), contract_info);
+    }
+
+    #[test]
+    fn migrate_restores_overwritten_config() {
+        let mut deps = mock_dependencies();
+        deps.storage.set(b"contract_info", br#"{"name":"marketplace","native_denom":"uaura"}"#);
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.1.0").unwrap();
+
+        let msg = MigrateMsg { name: None, native_denom: None };
+        let err = migrate(deps.as_mut(), mock_env(), msg).unwrap_err();
+        assert!(matches!(err, ContractError::MissingContractInfo {}));
+
+        let msg = MigrateMsg {
+            name: Some("marketplace".to_string()),
+            native_denom: Some(DENOM.to_string()),
+        };
+        migrate(deps.as_mut(), mock_env(), msg).unwrap();
+        let contract_info = CONTRACT_INFO.load(&deps.storage).unwrap();
+        assert_eq!(contract_info.name, "marketplace");
+        assert_eq!(contract_info.native_denom, DENOM);
+    }
+}
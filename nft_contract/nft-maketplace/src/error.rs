@@ -36,5 +36,44 @@ pub enum ContractError {
     BidExpiration {},
 
     #[error("NFTAddressNotMatch")]
-    NFTAddressNotMatch {}
+    NFTAddressNotMatch {},
+
+    #[error("BuyNowPriceBelowStartPrice")]
+    BuyNowPriceBelowStartPrice {},
+
+    #[error("AuctionNotStarted")]
+    AuctionNotStarted {},
+
+    #[error("StartTimeAfterExpiration")]
+    StartTimeAfterExpiration {},
+
+    #[error("Paused")]
+    Paused {},
+
+    #[error("NoPendingAdmin")]
+    NoPendingAdmin {},
+
+    #[error("CollectionSuspended")]
+    CollectionSuspended {},
+
+    #[error("InvalidFee")]
+    InvalidFee {},
+
+    #[error("SaleTypeNotAllowed")]
+    SaleTypeNotAllowed {},
+
+    #[error("PriceBelowMinimum")]
+    PriceBelowMinimum {},
+
+    #[error("NftNotTransferable")]
+    NftNotTransferable {},
+
+    #[error("NftRedeemed")]
+    NftRedeemed {},
+
+    #[error("MissingContractInfo")]
+    MissingContractInfo {},
+
+    #[error("RoyaltyExceedsPrice")]
+    RoyaltyExceedsPrice {},
 }
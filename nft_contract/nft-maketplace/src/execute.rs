@@ -1,34 +1,223 @@
 
-use cosmwasm_std::{Deps, DepsMut, Env, MessageInfo, Response, Uint128, from_binary, Addr, Coin, CosmosMsg, coin, BankMsg, WasmMsg, to_binary};
-use cw721::{Cw721ExecuteMsg, Cw721ReceiveMsg};
-use nft_base::msg::CollectionInfoResponse;
+use cosmwasm_std::{Binary, Deps, DepsMut, Env, MessageInfo, Response, StdError, StdResult, Uint128, from_binary, Addr, Coin, CosmosMsg, coin, BankMsg, WasmMsg, to_binary, Decimal, Empty, WasmQuery, ContractInfoResponse as WasmContractInfoResponse};
+use cw721::{Cw721ExecuteMsg, Cw721ReceiveMsg, Expiration};
+use nft_base::msg::{Cw2981QueryMsg, RoyaltiesInfoResponse, RoyaltyInfoResponse, RoyaltyPaymentsResponse, RoyaltyRecipientResponse, TokenStatusResponse};
 use nft_base::QueryMsg as NFTQueryMsg;
 
 
 use crate::error::ContractError;
-use crate::state::{CONTRACT_INFO, ADMIN, NFT_CONTRACTS, SaleType, increment_offerings, Offering, OFFERINGS, BidOffering, BID_OFFERINGS};
+use crate::msg::ListNftMsg;
+use crate::state::{CONTRACT_INFO, ADMIN, NFT_CONTRACTS, SaleType, increment_offerings, Offering, OFFERINGS, BidOffering, BID_OFFERINGS, PENDING_ADMIN, PAUSED, is_paused, CollectionConfig, TRUSTED_CODE_IDS, RoyaltyPolicy, REMOVED_NFT_CONTRACTS};
 
+const MAX_FEE: u64 = 10;
+/// Quoting a sale price of one whole unit at `Decimal` precision returns the royalty share exactly
+const ROYALTY_QUOTE_PRICE: u128 = 1_000_000_000_000_000_000;
 
 pub fn exec_add_nft_contract(
     deps: DepsMut, 
     _env: Env, 
     info: MessageInfo, 
-    address: String
+    address: String,
+    config: Option<CollectionConfig>
 ) -> Result<Response, ContractError> {
-    let admin = ADMIN.load(deps.storage)?;
-    if admin != info.sender {
-        return Err(ContractError::Unauthorized{});
-    }
+    assert_admin(&deps.as_ref(), &info)?;
 
-    let mut nft_contracts = NFT_CONTRACTS.load(deps.storage)?;
     let nft_contract = deps.api.addr_validate(&address)?;
-    
-    if !nft_contracts.contains(&nft_contract) {
-        nft_contracts.push(nft_contract);
-        NFT_CONTRACTS.save(deps.storage, &nft_contracts)?;
+    if let Some(config) = config {
+        validate_collection_config(&config)?;
+        // re-adding a suspended collection does not resume it
+        let suspended = NFT_CONTRACTS
+            .may_load(deps.storage, &nft_contract)?
+            .map_or(config.suspended, |stored| stored.suspended);
+        let config = CollectionConfig { suspended, ..config };
+        NFT_CONTRACTS.save(deps.storage, &nft_contract, &config)?;
+    } else if !NFT_CONTRACTS.has(deps.storage, &nft_contract) {
+        NFT_CONTRACTS.save(deps.storage, &nft_contract, &CollectionConfig::default())?;
     }
+    REMOVED_NFT_CONTRACTS.remove(deps.storage, &nft_contract);
+    Ok(Response::new()
+        .add_attribute("action", "add_nft_contract")
+        .add_attribute("nft_contract", nft_contract)
+    )
+}
+
+pub fn exec_add_code_id(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    code_id: u64
+) -> Result<Response, ContractError> {
+    assert_admin(&deps.as_ref(), &info)?;
+
+    TRUSTED_CODE_IDS.save(deps.storage, code_id, &Empty {})?;
     Ok(Response::new()
         .add_attribute("action", "add_code_id")
+        .add_attribute("code_id", code_id.to_string())
+    )
+}
+
+pub fn exec_remove_code_id(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    code_id: u64
+) -> Result<Response, ContractError> {
+    assert_admin(&deps.as_ref(), &info)?;
+
+    // collections already registered from this code id stay whitelisted
+    TRUSTED_CODE_IDS.remove(deps.storage, code_id);
+    Ok(Response::new()
+        .add_attribute("action", "remove_code_id")
+        .add_attribute("code_id", code_id.to_string())
+    )
+}
+
+pub fn exec_remove_nft_contract(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    address: String
+) -> Result<Response, ContractError> {
+    assert_admin(&deps.as_ref(), &info)?;
+
+    let nft_contract = deps.api.addr_validate(&address)?;
+    NFT_CONTRACTS.remove(deps.storage, &nft_contract);
+    // keep it from coming back through a trusted code id, only AddNFTContract restores it
+    REMOVED_NFT_CONTRACTS.save(deps.storage, &nft_contract, &Empty {})?;
+    Ok(Response::new()
+        .add_attribute("action", "remove_nft_contract")
+        .add_attribute("nft_contract", nft_contract)
+    )
+}
+
+pub fn exec_set_nft_contract_suspended(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    address: String,
+    suspended: bool
+) -> Result<Response, ContractError> {
+    assert_admin(&deps.as_ref(), &info)?;
+
+    let nft_contract = deps.api.addr_validate(&address)?;
+    let mut config = NFT_CONTRACTS.load(deps.storage, &nft_contract)?;
+    config.suspended = suspended;
+    NFT_CONTRACTS.save(deps.storage, &nft_contract, &config)?;
+
+    let action = if suspended { "suspend_nft_contract" } else { "resume_nft_contract" };
+    Ok(Response::new()
+        .add_attribute("action", action)
+        .add_attribute("nft_contract", nft_contract)
+    )
+}
+
+pub fn exec_update_collection_config(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    address: String,
+    config: CollectionConfig
+) -> Result<Response, ContractError> {
+    assert_admin(&deps.as_ref(), &info)?;
+
+    let nft_contract = deps.api.addr_validate(&address)?;
+    let stored = NFT_CONTRACTS.load(deps.storage, &nft_contract)?;
+    validate_collection_config(&config)?;
+    // suspension is only changed through Suspend/ResumeNFTContract
+    let config = CollectionConfig { suspended: stored.suspended, ..config };
+    NFT_CONTRACTS.save(deps.storage, &nft_contract, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_collection_config")
+        .add_attribute("nft_contract", nft_contract)
+    )
+}
+
+pub fn exec_set_paused(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    paused: bool
+) -> Result<Response, ContractError> {
+    assert_admin(&deps.as_ref(), &info)?;
+
+    PAUSED.save(deps.storage, &paused)?;
+    let action = if paused { "pause" } else { "unpause" };
+    Ok(Response::new()
+        .add_attribute("action", action)
+        .add_attribute("sender", info.sender)
+    )
+}
+
+pub fn exec_update_config(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    name: Option<String>,
+    native_denom: Option<String>,
+    fee: Option<Decimal>,
+    royalty_policy: Option<RoyaltyPolicy>
+) -> Result<Response, ContractError> {
+    assert_admin(&deps.as_ref(), &info)?;
+
+    let mut contract_info = CONTRACT_INFO.load(deps.storage)?;
+    if let Some(name) = name {
+        contract_info.name = name;
+    }
+    // open listings keep the denom they were created with
+    if let Some(native_denom) = native_denom {
+        contract_info.native_denom = native_denom;
+    }
+    if let Some(fee) = fee {
+        validate_fee(fee)?;
+        contract_info.fee = fee;
+    }
+    if let Some(royalty_policy) = royalty_policy {
+        contract_info.royalty_policy = royalty_policy;
+    }
+    CONTRACT_INFO.save(deps.storage, &contract_info)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_config")
+        .add_attribute("name", contract_info.name)
+        .add_attribute("native_denom", contract_info.native_denom)
+        .add_attribute("fee", contract_info.fee.to_string())
+        .add_attribute("royalty_policy", format!("{:?}", contract_info.royalty_policy))
+    )
+}
+
+pub fn exec_propose_admin(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    address: String
+) -> Result<Response, ContractError> {
+    assert_admin(&deps.as_ref(), &info)?;
+
+    let pending_admin = deps.api.addr_validate(&address)?;
+    PENDING_ADMIN.save(deps.storage, &pending_admin)?;
+    Ok(Response::new()
+        .add_attribute("action", "propose_admin")
+        .add_attribute("admin", info.sender)
+        .add_attribute("pending_admin", pending_admin)
+    )
+}
+
+pub fn exec_accept_admin(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let pending_admin = PENDING_ADMIN.may_load(deps.storage)?.ok_or(ContractError::NoPendingAdmin {})?;
+    if pending_admin != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    ADMIN.save(deps.storage, &pending_admin)?;
+    PENDING_ADMIN.remove(deps.storage);
+    Ok(Response::new()
+        .add_attribute("action", "accept_admin")
+        .add_attribute("admin", pending_admin)
     )
 }
 
@@ -38,59 +227,18 @@ pub fn exec_make_offer(
     info: MessageInfo, 
     offering_id: String
 ) -> Result<Response, ContractError> {
+    assert_not_paused(&deps.as_ref())?;
     let offer = OFFERINGS.load(deps.storage, &offering_id)?;
+    load_active_collection(&deps.as_ref(), &offer.nft_address)?;
     if let SaleType::FixedPrice(price)  = offer.sale_type {
-        let royalty_info = offer.royalty_info;
-
-        let funds_from_sender = one_coin(&deps.as_ref(), &info)?;
+        let funds_from_sender = one_coin(&info, &offer.denom)?;
         let Coin {amount, denom} = funds_from_sender;
         if amount < price {
             return Err(ContractError::InsufficientDeposit {});
         }
-        
-        let mut cosmos_msg:Vec<CosmosMsg> = vec![];
-        let royalty_fee;
-        let net_price;
-    
-        if royalty_info.is_some() {
-            let creator_address = royalty_info.clone().unwrap().payment_address;
-            let share = royalty_info.unwrap().share;
-    
-            royalty_fee = amount * share;
-            net_price = amount - amount * share;
-    
-            // send price to creator
-            let transfer_royalty_fee_msg = CosmosMsg::Bank(BankMsg::Send {
-                to_address: creator_address,
-                amount: vec![coin(royalty_fee.u128(), denom.clone())],
-            });
-    
-            cosmos_msg.push(transfer_royalty_fee_msg);
-        
-        }else {
-            royalty_fee = Uint128::from(0u128);
-            net_price = amount;
-        }
-
-        let transfer_cw721_msg = Cw721ExecuteMsg::TransferNft {
-            recipient: info.sender.clone().into_string(),
-            token_id: offer.token_id.clone(),
-        };
-    
-        let exec_cw721_transfer = WasmMsg::Execute {
-            contract_addr: (&offer.nft_address).to_string(),
-            msg: to_binary(&transfer_cw721_msg)?,
-            funds: vec![],
-        };
-        let cw721_transfer_cosmos_msg: CosmosMsg =  exec_cw721_transfer.into();
-        // send price to seller
-        let transfer_net_price_msg: CosmosMsg = CosmosMsg::Bank(BankMsg::Send {
-            to_address: (&offer.seller).to_string(),
-            amount: vec![coin(net_price.u128(), denom.clone())],
-        }).into();
 
-        cosmos_msg.push(transfer_net_price_msg);
-        cosmos_msg.push(cw721_transfer_cosmos_msg);
+        let Settlement { messages: cosmos_msg, net_price, royalty_fee, market_fee } =
+            settle_sale(&deps.as_ref(), &offer, &info.sender, amount, &denom)?;
 
         OFFERINGS.remove(deps.storage, &offering_id);
 
@@ -104,7 +252,8 @@ pub fn exec_make_offer(
             .add_attribute("token_id", offer.token_id)
             .add_attribute("contract_addr", offer.nft_address.to_string())
             .add_attribute("net_price", net_price)
-            .add_attribute("royalty_fee", royalty_fee))
+            .add_attribute("royalty_fee", royalty_fee)
+            .add_attribute("market_fee", market_fee))
     }  else {
         Err(ContractError::SaleTypeMustBeFixedPrice {})
     }
@@ -116,18 +265,55 @@ pub fn exec_bid(
     info: MessageInfo, 
     offering_id: String,
 ) -> Result<Response, ContractError> {
+    assert_not_paused(&deps.as_ref())?;
     let offer = OFFERINGS.load(deps.storage, &offering_id)?;
-    if let SaleType::Auction(bid)  = offer.sale_type {
+    load_active_collection(&deps.as_ref(), &offer.nft_address)?;
+    if let SaleType::Auction(bid)  = offer.sale_type.clone() {
         if bid.expiration.is_expired(&env.block) {
             return Err(ContractError::BidExpiration {});
         }
         let mut bid_offering = BID_OFFERINGS.load(deps.storage, &offering_id)?;
+        if env.block.time < bid_offering.start_timestamp {
+            return Err(ContractError::AuctionNotStarted {});
+        }
 
-        let funds_from_sender = one_coin(&deps.as_ref(), &info)?;
+        let funds_from_sender = one_coin(&info, &offer.denom)?;
         let Coin {amount, denom} = funds_from_sender;
         
         let mut cosmos_msg:Vec<CosmosMsg> = vec![];
 
+        if let Some(buy_now_price) = bid.buy_now_price {
+            if buy_now_price <= amount {
+                // refund the standing highest bidder before settling
+                if let Some(bidder) = bid_offering.address.clone() {
+                    let refund_bid_msg: CosmosMsg = CosmosMsg::Bank(BankMsg::Send {
+                        to_address: bidder.to_string(),
+                        amount: vec![coin(bid_offering.highest_price().u128(), denom.clone())],
+                    });
+                    cosmos_msg.push(refund_bid_msg);
+                }
+
+                let settlement = settle_sale(&deps.as_ref(), &offer, &info.sender, amount, &denom)?;
+                cosmos_msg.extend(settlement.messages);
+
+                OFFERINGS.remove(deps.storage, &offering_id);
+                BID_OFFERINGS.remove(deps.storage, &offering_id);
+
+                let price_string = format!("{} {}", amount, denom);
+                return Ok(Response::new()
+                    .add_messages(cosmos_msg)
+                    .add_attribute("action", "buy_now")
+                    .add_attribute("seller", offer.seller.to_string())
+                    .add_attribute("buyer", info.sender)
+                    .add_attribute("paid_price", price_string)
+                    .add_attribute("token_id", offer.token_id)
+                    .add_attribute("contract_addr", offer.nft_address.to_string())
+                    .add_attribute("net_price", settlement.net_price)
+                    .add_attribute("royalty_fee", settlement.royalty_fee)
+                    .add_attribute("market_fee", settlement.market_fee));
+            }
+        }
+
         if bid_offering.highest_bid_price.is_none(){
             if bid.start_price <= amount{
                 bid_offering.new(amount, info.sender.clone());
@@ -144,6 +330,8 @@ pub fn exec_bid(
                 bid_offering.new(amount, info.sender.clone());
             } else {return Err(ContractError::InsufficientDeposit {  });}
         }
+        BID_OFFERINGS.save(deps.storage, &offering_id, &bid_offering)?;
+
         let price_string = format!("{} {}", amount, denom);
         Ok(Response::new()
             .add_messages(cosmos_msg)
@@ -166,7 +354,7 @@ pub fn exec_close_bid(
 ) -> Result<Response, ContractError> {
     let offer = OFFERINGS.load(deps.storage, &offering_id)?;
     
-    if let SaleType::Auction(bid)  = offer.sale_type {
+    if let SaleType::Auction(bid)  = offer.sale_type.clone() {
         let bid_offering = BID_OFFERINGS.load(deps.storage, &offering_id)?;
 
         if info.sender == offer.seller {
@@ -181,63 +369,31 @@ pub fn exec_close_bid(
                 funds: vec![],
             };
     
-            let cw721_transfer_cosmos_msg: Vec<CosmosMsg> = vec![exec_cw721_transfer.into()];
+            let mut cosmos_msg: Vec<CosmosMsg> = vec![exec_cw721_transfer.into()];
+            // the standing bid is held in escrow, give it back to the bidder
+            if let Some(bidder) = bid_offering.address.clone() {
+                let refund_bid_msg: CosmosMsg = CosmosMsg::Bank(BankMsg::Send {
+                    to_address: bidder.to_string(),
+                    amount: vec![coin(bid_offering.highest_price().u128(), offer.denom.clone())],
+                });
+                cosmos_msg.push(refund_bid_msg);
+            }
             OFFERINGS.remove(deps.storage, &offering_id);
+            BID_OFFERINGS.remove(deps.storage, &offering_id);
     
-            return Ok(Response::new().add_messages(cw721_transfer_cosmos_msg)
+            return Ok(Response::new().add_messages(cosmos_msg)
                 .add_attribute("action", "seller_close_bid")
                 .add_attribute("seller", info.sender)
                 .add_attribute("offering_id", offering_id))
         } else if (Some(info.sender.clone()) == bid_offering.address) && bid.expiration.is_expired(&env.block){
-            let royalty_info = offer.royalty_info;
             let amount = bid_offering.highest_price();
-            let denom = CONTRACT_INFO.load(deps.storage)?.native_denom;
+            let denom = offer.denom.clone();
 
-            let mut cosmos_msg:Vec<CosmosMsg> = vec![];
-            let royalty_fee;
-            let net_price;
-        
-            if royalty_info.is_some() {
-                let creator_address = royalty_info.clone().unwrap().payment_address;
-                let share = royalty_info.unwrap().share;
-        
-                royalty_fee = amount * share;
-                net_price = amount - amount * share;
-        
-                // send price to creator
-                let transfer_royalty_fee_msg = CosmosMsg::Bank(BankMsg::Send {
-                    to_address: creator_address,
-                    amount: vec![coin(royalty_fee.u128(), denom.clone())],
-                });
-        
-                cosmos_msg.push(transfer_royalty_fee_msg);
-            
-            }else {
-                royalty_fee = Uint128::from(0u128);
-                net_price = amount;
-            }
+            let Settlement { messages: cosmos_msg, net_price, royalty_fee, market_fee } =
+                settle_sale(&deps.as_ref(), &offer, &info.sender, amount, &denom)?;
 
-            let transfer_cw721_msg = Cw721ExecuteMsg::TransferNft {
-                recipient: info.sender.clone().into_string(),
-                token_id: offer.token_id.clone(),
-            };
-        
-            let exec_cw721_transfer = WasmMsg::Execute {
-                contract_addr: (&offer.nft_address).to_string(),
-                msg: to_binary(&transfer_cw721_msg)?,
-                funds: vec![],
-            };
-            let cw721_transfer_cosmos_msg: CosmosMsg =  exec_cw721_transfer.into();
-            // send price to seller
-            let transfer_net_price_msg: CosmosMsg = CosmosMsg::Bank(BankMsg::Send {
-                to_address: (&offer.seller).to_string(),
-                amount: vec![coin(net_price.u128(), denom.clone())],
-            }).into();
-    
-            cosmos_msg.push(transfer_net_price_msg);
-            cosmos_msg.push(cw721_transfer_cosmos_msg);
-    
             OFFERINGS.remove(deps.storage, &offering_id);
+            BID_OFFERINGS.remove(deps.storage, &offering_id);
 
             let price_string = format!("{} {}", amount, denom);
             Ok(Response::new()
@@ -249,7 +405,8 @@ pub fn exec_close_bid(
                 .add_attribute("token_id", offer.token_id)
                 .add_attribute("contract_addr", offer.nft_address.to_string())
                 .add_attribute("net_price", net_price)
-                .add_attribute("royalty_fee", royalty_fee))
+                .add_attribute("royalty_fee", royalty_fee)
+                .add_attribute("market_fee", market_fee))
         } else {
             Err(ContractError::Unauthorized {  })
         }
@@ -273,10 +430,14 @@ pub fn exec_update_price(
         if update_price.is_zero() {
             return Err(ContractError::PriceMustBePosiTive {});
         }
+        let config = NFT_CONTRACTS.may_load(deps.storage, &offer.nft_address)?.unwrap_or_default();
+        if config.min_price.is_some_and(|min_price| update_price < min_price) {
+            return Err(ContractError::PriceBelowMinimum {});
+        }
         offer.sale_type = SaleType::FixedPrice(update_price);
         OFFERINGS.save(deps.storage, &offering_id, &offer)?;
     
-        let price_string = format!("{} {}", update_price, CONTRACT_INFO.load(deps.storage)?.native_denom);
+        let price_string = format!("{} {}", update_price, offer.denom);
 
         Ok(Response::new()
         .add_attribute("action", "update_price")
@@ -289,34 +450,62 @@ pub fn exec_update_price(
 }
 
 pub fn exec_receive_nft(
-    deps: DepsMut,
+    mut deps: DepsMut,
     env: Env,
     info: MessageInfo,
     rcv_msg: Cw721ReceiveMsg,
 ) -> Result<Response, ContractError> {
-    let nft_addresses = NFT_CONTRACTS.load(deps.storage)?;
-    if !nft_addresses.contains(&info.sender){
-        return Err(ContractError::NFTAddressNotMatch {  })
-    } 
-    let msg: SaleType = from_binary(&rcv_msg.msg)?;
+    assert_not_paused(&deps.as_ref())?;
+    let config = match NFT_CONTRACTS.may_load(deps.storage, &info.sender)? {
+        Some(config) => config,
+        None => register_trusted_collection(&mut deps, &info.sender)?,
+    };
+    if config.suspended {
+        return Err(ContractError::CollectionSuspended {});
+    }
+    let ListNftMsg { sale_type: msg, denom } = parse_list_nft_msg(&rcv_msg.msg)?;
+    let native_denom = CONTRACT_INFO.load(deps.storage)?.native_denom;
+    let denom = denom.unwrap_or(native_denom.clone());
+    if !config.accepts_denom(&denom, &native_denom) {
+        return Err(ContractError::DenomNotMatch {});
+    }
     let token_id: String = rcv_msg.token_id;
     let seller: Addr = deps.api.addr_validate(&rcv_msg.sender)?;
+    assert_token_tradable(&deps.as_ref(), &info.sender, &token_id)?;
 
     let id = increment_offerings(deps.storage)?.to_string();
-    let collection_info: CollectionInfoResponse = deps
-        .querier
-        .query_wasm_smart(info.sender.clone(), &NFTQueryMsg::CollectionInfo {})?;
-    
-    let royalty_info = collection_info.royalty_info;
+    let royalty_info = query_royalty_info(&deps.as_ref(), &info.sender, &token_id)?;
 
     match msg {
         SaleType::FixedPrice(price) => {
+            if !config.allow_fixed_price {
+                return Err(ContractError::SaleTypeNotAllowed {});
+            }
             if price.is_zero() {
                 return Err(ContractError::PriceMustBePosiTive {});
             }
+            if config.min_price.is_some_and(|min_price| price < min_price) {
+                return Err(ContractError::PriceBelowMinimum {});
+            }
         },
-        SaleType::Auction(_) => {
-            let bid_offering = BidOffering::default(&env);
+        SaleType::Auction(ref bid) => {
+            if !config.allow_auction {
+                return Err(ContractError::SaleTypeNotAllowed {});
+            }
+            if config.min_price.is_some_and(|min_price| bid.start_price < min_price) {
+                return Err(ContractError::PriceBelowMinimum {});
+            }
+            if let Some(buy_now_price) = bid.buy_now_price {
+                if buy_now_price < bid.start_price {
+                    return Err(ContractError::BuyNowPriceBelowStartPrice {});
+                }
+            }
+            if let (Some(start_time), Expiration::AtTime(end_time)) = (bid.start_time, bid.expiration) {
+                if start_time >= end_time {
+                    return Err(ContractError::StartTimeAfterExpiration {});
+                }
+            }
+            let bid_offering = BidOffering::default(&env, bid.start_time);
             BID_OFFERINGS.save(deps.storage, &id, &bid_offering)?;
         },
     }
@@ -327,6 +516,7 @@ pub fn exec_receive_nft(
         seller,
         sale_type: msg,
         listing_time: env.block.time,
+        denom,
     };
     OFFERINGS.save(deps.storage, &id, &offer)?;
         Ok(Response::new()
@@ -373,14 +563,183 @@ pub fn exec_withdraw_nft(
 }
 
 
-pub fn one_coin(deps: &Deps, info: &MessageInfo) -> Result<Coin, ContractError> {
+pub struct Settlement {
+    pub messages: Vec<CosmosMsg>,
+    pub net_price: Uint128,
+    pub royalty_fee: Uint128,
+    pub market_fee: Uint128,
+}
+
+/// Pays the royalty, the marketplace fee and the seller out of `amount` and hands the NFT over to `buyer`
+pub fn settle_sale(
+    deps: &Deps,
+    offer: &Offering,
+    buyer: &Addr,
+    amount: Uint128,
+    denom: &str,
+) -> Result<Settlement, ContractError> {
+    let config = NFT_CONTRACTS.may_load(deps.storage, &offer.nft_address)?.unwrap_or_default();
+    let contract_info = CONTRACT_INFO.load(deps.storage)?;
+    let mut cosmos_msg:Vec<CosmosMsg> = vec![];
+    let mut royalty_fee = Uint128::from(0u128);
+
+    let royalty_payments = match contract_info.royalty_policy {
+        RoyaltyPolicy::Snapshot => offer.royalty_info.clone().map_or(vec![], |royalty_info| {
+            royalty_info.recipients.into_iter().map(|recipient| RoyaltiesInfoResponse {
+                address: recipient.payment_address,
+                royalty_amount: amount * recipient.share,
+            }).collect()
+        }),
+        RoyaltyPolicy::Live => query_royalty_payments(deps, &offer.nft_address, &offer.token_id, amount)?,
+    };
+    if config.enforce_royalties {
+        for royalty in royalty_payments {
+            if royalty.royalty_amount.is_zero() {
+                continue;
+            }
+            royalty_fee = royalty_fee.checked_add(royalty.royalty_amount).map_err(StdError::from)?;
+
+            // send price to each royalty recipient
+            let transfer_royalty_fee_msg = CosmosMsg::Bank(BankMsg::Send {
+                to_address: royalty.address,
+                amount: vec![coin(royalty.royalty_amount.u128(), denom)],
+            });
+
+            cosmos_msg.push(transfer_royalty_fee_msg);
+        }
+    }
+
+    let fee = config.fee.unwrap_or(contract_info.fee);
+    let market_fee = amount * fee;
+    if !market_fee.is_zero() {
+        // send fee to marketplace admin
+        let transfer_market_fee_msg = CosmosMsg::Bank(BankMsg::Send {
+            to_address: ADMIN.load(deps.storage)?.to_string(),
+            amount: vec![coin(market_fee.u128(), denom)],
+        });
+
+        cosmos_msg.push(transfer_market_fee_msg);
+    }
+    let net_price = amount
+        .checked_sub(royalty_fee)
+        .and_then(|price| price.checked_sub(market_fee))
+        .map_err(|_| ContractError::RoyaltyExceedsPrice {})?;
+
+    let transfer_cw721_msg = Cw721ExecuteMsg::TransferNft {
+        recipient: buyer.to_string(),
+        token_id: offer.token_id.clone(),
+    };
+
+    let exec_cw721_transfer = WasmMsg::Execute {
+        contract_addr: (&offer.nft_address).to_string(),
+        msg: to_binary(&transfer_cw721_msg)?,
+        funds: vec![],
+    };
+    let cw721_transfer_cosmos_msg: CosmosMsg =  exec_cw721_transfer.into();
+    // send price to seller
+    let transfer_net_price_msg: CosmosMsg = CosmosMsg::Bank(BankMsg::Send {
+        to_address: (&offer.seller).to_string(),
+        amount: vec![coin(net_price.u128(), denom)],
+    });
+
+    cosmos_msg.push(transfer_net_price_msg);
+    cosmos_msg.push(cw721_transfer_cosmos_msg);
+
+    Ok(Settlement {
+        messages: cosmos_msg,
+        net_price,
+        royalty_fee,
+        market_fee,
+    })
+}
+
+/// Queries the royalty owed to each recipient when `token_id` sells for `sale_price`.
+/// Collections without multi-recipient royalties are asked the standard CW2981 query, and
+/// those supporting neither pay no royalty. Any other query error is returned
+pub fn query_royalty_payments(
+    deps: &Deps,
+    nft_address: &Addr,
+    token_id: &str,
+    sale_price: Uint128,
+) -> Result<Vec<RoyaltiesInfoResponse>, ContractError> {
+    let msg = NFTQueryMsg::RoyaltyPayments { token_id: token_id.to_string(), sale_price };
+    match deps.querier.query_wasm_smart::<RoyaltyPaymentsResponse>(nft_address.to_string(), &msg) {
+        Ok(royalty) => return Ok(royalty.payments),
+        Err(err) if !is_unsupported_query(&err) => return Err(err.into()),
+        Err(_) => {}
+    }
+
+    let msg = NFTQueryMsg::Extension {
+        msg: Cw2981QueryMsg::RoyaltyInfo { token_id: token_id.to_string(), sale_price },
+    };
+    match deps.querier.query_wasm_smart::<RoyaltiesInfoResponse>(nft_address.to_string(), &msg) {
+        Ok(royalty) if !royalty.royalty_amount.is_zero() && !royalty.address.is_empty() => Ok(vec![royalty]),
+        Ok(_) => Ok(vec![]),
+        Err(err) if is_unsupported_query(&err) => Ok(vec![]),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// A collection rejects query variants it does not know while parsing the message, and a
+/// vanilla cw721-base answers the CW2981 extension with an empty response
+fn is_unsupported_query(err: &StdError) -> bool {
+    match err {
+        StdError::ParseErr { .. } => true,
+        err => err.to_string().contains("unknown variant"),
+    }
+}
+
+/// Queries the royalty of `token_id` as a share of the sale price for each recipient
+pub fn query_royalty_info(deps: &Deps, nft_address: &Addr, token_id: &str) -> Result<Option<RoyaltyInfoResponse>, ContractError> {
+    let quote_price = Uint128::new(ROYALTY_QUOTE_PRICE);
+    let payments = query_royalty_payments(deps, nft_address, token_id, quote_price)?;
+    if payments.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(RoyaltyInfoResponse {
+        recipients: payments.into_iter().map(|payment| RoyaltyRecipientResponse {
+            payment_address: payment.address,
+            share: Decimal::from_ratio(payment.royalty_amount, quote_price),
+        }).collect(),
+    }))
+}
+
+/// Refuses tokens that could never be delivered to a buyer: non-transferable ones and those
+/// whose physical pair is being or has been redeemed. Collections that cannot report a token
+/// status are let through
+fn assert_token_tradable(deps: &Deps, nft_address: &Addr, token_id: &str) -> Result<(), ContractError> {
+    let msg = NFTQueryMsg::TokenStatus { token_id: token_id.to_string() };
+    let status: TokenStatusResponse = match deps.querier.query_wasm_smart(nft_address.to_string(), &msg) {
+        Ok(status) => status,
+        Err(err) if is_unsupported_query(&err) => return Ok(()),
+        Err(err) => return Err(err.into()),
+    };
+    if !status.transferable {
+        return Err(ContractError::NftNotTransferable {});
+    }
+    if status.redemption.is_some() {
+        return Err(ContractError::NftRedeemed {});
+    }
+    Ok(())
+}
+
+/// Reads a `ListNftMsg`, falling back to the plain `SaleType` payload used before denoms
+pub fn parse_list_nft_msg(msg: &Binary) -> StdResult<ListNftMsg> {
+    from_binary::<ListNftMsg>(msg).or_else(|err| {
+        from_binary::<SaleType>(msg)
+            .map(|sale_type| ListNftMsg { sale_type, denom: None })
+            .map_err(|_| err)
+    })
+}
+
+pub fn one_coin(info: &MessageInfo, denom: &str) -> Result<Coin, ContractError> {
     match info.funds.len() {
         0 => Err(ContractError::NoFunds {}),
         1 => {
             let coin = &info.funds[0];
             if coin.amount.is_zero() {
                 Err(ContractError::NoFunds {})
-            } else if coin.denom == CONTRACT_INFO.load(deps.storage)?.native_denom {
+            } else if coin.denom != denom {
                 Err(ContractError::DenomNotMatch {})
             } else {
                 Ok(coin.clone())
@@ -388,4 +747,62 @@ pub fn one_coin(deps: &Deps, info: &MessageInfo) -> Result<Coin, ContractError>
         }
         _ => Err(ContractError::MultipleDenoms {}),
     }
-}
\ No newline at end of file
+}
+
+pub fn validate_fee(fee: Decimal) -> Result<(), ContractError> {
+    if fee > Decimal::percent(MAX_FEE) {
+        return Err(ContractError::InvalidFee {});
+    }
+    Ok(())
+}
+
+fn validate_collection_config(config: &CollectionConfig) -> Result<(), ContractError> {
+    if let Some(fee) = config.fee {
+        validate_fee(fee)?;
+    }
+    Ok(())
+}
+
+fn assert_admin(deps: &Deps, info: &MessageInfo) -> Result<(), ContractError> {
+    let admin = ADMIN.load(deps.storage)?;
+    if admin != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
+
+/// Loads the config of a whitelisted collection that is not suspended
+fn load_active_collection(deps: &Deps, nft_address: &Addr) -> Result<CollectionConfig, ContractError> {
+    let config = NFT_CONTRACTS
+        .may_load(deps.storage, nft_address)?
+        .ok_or(ContractError::NFTAddressNotMatch {})?;
+    if config.suspended {
+        return Err(ContractError::CollectionSuspended {});
+    }
+    Ok(config)
+}
+
+/// Whitelists an unknown collection if it was instantiated from a trusted code id and was not
+/// removed by the admin before
+fn register_trusted_collection(deps: &mut DepsMut, nft_address: &Addr) -> Result<CollectionConfig, ContractError> {
+    if REMOVED_NFT_CONTRACTS.has(deps.storage, nft_address) {
+        return Err(ContractError::NFTAddressNotMatch {});
+    }
+    let contract_info: WasmContractInfoResponse = deps.querier.query(
+        &WasmQuery::ContractInfo { contract_addr: nft_address.to_string() }.into()
+    )?;
+    if !TRUSTED_CODE_IDS.has(deps.storage, contract_info.code_id) {
+        return Err(ContractError::NFTAddressNotMatch {});
+    }
+
+    let config = CollectionConfig::default();
+    NFT_CONTRACTS.save(deps.storage, nft_address, &config)?;
+    Ok(config)
+}
+
+fn assert_not_paused(deps: &Deps) -> Result<(), ContractError> {
+    if is_paused(deps.storage)? {
+        return Err(ContractError::Paused {});
+    }
+    Ok(())
+}
@@ -4,5 +4,5 @@ pub mod msg;
 pub mod state;
 mod execute;
 mod query;
-mod package;
+pub mod package;
 pub use crate::error::ContractError;
@@ -1,31 +1,68 @@
-use cosmwasm_std::Uint128;
+use cosmwasm_std::{Decimal, Uint128};
 use cw721::Cw721ReceiveMsg;
 use serde::{Serialize, Deserialize};
 use schemars::JsonSchema;
 
+use crate::state::{AuctionStatus, CollectionConfig, RoyaltyPolicy, SaleType};
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     pub admin: Option<String>,
     pub name: String,
     pub native_denom: String,
+    pub fee: Option<Decimal>,
+    /// Defaults to snapshotting the royalty at listing time
+    pub royalty_policy: Option<RoyaltyPolicy>,
     pub nft_contracts: Vec<String>,
 }
 
+/// Payload of the `Cw721ReceiveMsg` that lists the received NFT. A plain `SaleType` payload
+/// is still accepted and priced in the native denom
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ListNftMsg {
+    pub sale_type: SaleType,
+    /// Defaults to the native denom
+    pub denom: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {
+    /// Only needed when the stored config was lost to the contract version
+    pub name: Option<String>,
+    pub native_denom: Option<String>,
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
-    AddNFTContract { address: String},
+    AddNFTContract { address: String, config: Option<CollectionConfig> },
+    UpdateCollectionConfig { address: String, config: CollectionConfig },
+    RemoveNFTContract { address: String },
+    SuspendNFTContract { address: String },
+    ResumeNFTContract { address: String },
+    AddCodeId { code_id: u64 },
+    RemoveCodeId { code_id: u64 },
     WithdrawNft { offering_id: String},
     MakeOffer { offering_id: String},
     Bid{ offering_id: String },
     CloseBid { offering_id: String},
     UpdatePrice { offering_id: String, update_price: Uint128},
     ReceiveNft(Cw721ReceiveMsg),
+    Pause {},
+    Unpause {},
+    UpdateConfig { name: Option<String>, native_denom: Option<String>, fee: Option<Decimal>, royalty_policy: Option<RoyaltyPolicy> },
+    ProposeAdmin { address: String },
+    AcceptAdmin {},
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
-    
+    Config {},
+    NftContract { address: String },
+    NftContracts { start_after: Option<String>, limit: Option<u32> },
+    CodeIds { start_after: Option<u64>, limit: Option<u32> },
+    Offering { offering_id: String },
+    Offerings { start_after: Option<String>, limit: Option<u32> },
+    Auctions { status: Option<AuctionStatus>, start_after: Option<String>, limit: Option<u32> },
 }
@@ -1,14 +1,19 @@
-use cosmwasm_std::Timestamp;
+use cosmwasm_std::{Addr, Decimal, Timestamp, Uint128};
 use nft_base::msg::RoyaltyInfoResponse;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::state::SaleType;
+use crate::state::{AuctionStatus, Bid, CollectionConfig, RoyaltyPolicy, SaleType};
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct ContractInfoResponse {
     pub name: String,
-    pub native_denom: String
+    pub native_denom: String,
+    pub fee: Decimal,
+    pub royalty_policy: RoyaltyPolicy,
+    pub admin: String,
+    pub pending_admin: Option<String>,
+    pub paused: bool,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -20,9 +25,46 @@ pub struct QueryOfferingsResult {
     pub nft_address: String,
     pub seller: String,
     pub listing_time: Timestamp,
+    pub denom: String,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct OfferingsResponse {
     pub offerings: Vec<QueryOfferingsResult>,
-}
\ No newline at end of file
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct QueryAuctionResult {
+    pub id: String,
+    pub token_id: String,
+    pub bid: Bid,
+    pub nft_address: String,
+    pub seller: String,
+    pub highest_bid_price: Option<Uint128>,
+    pub highest_bidder: Option<Addr>,
+    pub start_timestamp: Timestamp,
+    pub status: AuctionStatus,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AuctionsResponse {
+    pub auctions: Vec<QueryAuctionResult>,
+    /// Last auction looked at, pass it as `start_after` to continue. Unset once all were scanned
+    pub last_scanned: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct NftContractResponse {
+    pub address: String,
+    pub config: CollectionConfig,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct NftContractsResponse {
+    pub nft_contracts: Vec<NftContractResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CodeIdsResponse {
+    pub code_ids: Vec<u64>,
+}
@@ -0,0 +1,153 @@
+use cosmwasm_std::{Deps, Env, Order, StdResult};
+use cw_storage_plus::Bound;
+
+use crate::package::{
+    AuctionsResponse, CodeIdsResponse, ContractInfoResponse, NftContractResponse, NftContractsResponse,
+    OfferingsResponse, QueryAuctionResult, QueryOfferingsResult,
+};
+use crate::state::{
+    is_paused, AuctionStatus, Offering, SaleType, ADMIN, BID_OFFERINGS, CONTRACT_INFO,
+    NFT_CONTRACTS, OFFERINGS, PENDING_ADMIN, TRUSTED_CODE_IDS,
+};
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+pub fn query_config(deps: Deps) -> StdResult<ContractInfoResponse> {
+    let info = CONTRACT_INFO.load(deps.storage)?;
+    Ok(ContractInfoResponse {
+        name: info.name,
+        native_denom: info.native_denom,
+        fee: info.fee,
+        royalty_policy: info.royalty_policy,
+        admin: ADMIN.load(deps.storage)?.to_string(),
+        pending_admin: PENDING_ADMIN.may_load(deps.storage)?.map(|addr| addr.to_string()),
+        paused: is_paused(deps.storage)?,
+    })
+}
+
+pub fn query_nft_contract(deps: Deps, address: String) -> StdResult<NftContractResponse> {
+    let nft_contract = deps.api.addr_validate(&address)?;
+    let config = NFT_CONTRACTS.load(deps.storage, &nft_contract)?;
+    Ok(NftContractResponse { address, config })
+}
+
+pub fn query_nft_contracts(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<NftContractsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_addr = start_after.map(|addr| deps.api.addr_validate(&addr)).transpose()?;
+    let start = start_addr.as_ref().map(Bound::exclusive);
+
+    let nft_contracts: StdResult<Vec<NftContractResponse>> = NFT_CONTRACTS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(address, config)| NftContractResponse {
+            address: address.to_string(),
+            config,
+        }))
+        .collect();
+
+    Ok(NftContractsResponse { nft_contracts: nft_contracts? })
+}
+
+pub fn query_code_ids(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<CodeIdsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(Bound::exclusive);
+
+    let code_ids: StdResult<Vec<u64>> = TRUSTED_CODE_IDS
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect();
+
+    Ok(CodeIdsResponse { code_ids: code_ids? })
+}
+
+pub fn query_offering(deps: Deps, offering_id: String) -> StdResult<QueryOfferingsResult> {
+    let offer = OFFERINGS.load(deps.storage, &offering_id)?;
+    Ok(parse_offering(offering_id, offer))
+}
+
+pub fn query_offerings(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<OfferingsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+
+    let offerings: StdResult<Vec<QueryOfferingsResult>> = OFFERINGS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(id, offer)| parse_offering(id, offer)))
+        .collect();
+
+    Ok(OfferingsResponse { offerings: offerings? })
+}
+
+/// auctions returns the auction listings, optionally only those in the given status.
+/// At most `limit` auctions are scanned per call, so a filtered page can come back short
+pub fn query_auctions(
+    deps: Deps,
+    env: Env,
+    status: Option<AuctionStatus>,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<AuctionsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+
+    // every auction has a bid offering, so only auctions are scanned
+    let scanned = BID_OFFERINGS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    let last_scanned = if scanned.len() == limit {
+        scanned.last().map(|(id, _)| id.clone())
+    } else {
+        None
+    };
+
+    let mut auctions = vec![];
+    for (id, bid_offering) in scanned {
+        let offer = OFFERINGS.load(deps.storage, &id)?;
+        if let SaleType::Auction(bid) = offer.sale_type {
+            let auction_status = bid_offering.status(&bid, &env.block);
+            if status.is_some() && status != Some(auction_status.clone()) {
+                continue;
+            }
+            auctions.push(QueryAuctionResult {
+                id,
+                token_id: offer.token_id,
+                bid,
+                nft_address: offer.nft_address.to_string(),
+                seller: offer.seller.to_string(),
+                highest_bid_price: bid_offering.highest_bid_price,
+                highest_bidder: bid_offering.address,
+                start_timestamp: bid_offering.start_timestamp,
+                status: auction_status,
+            });
+        }
+    }
+
+    Ok(AuctionsResponse { auctions, last_scanned })
+}
+
+fn parse_offering(id: String, offer: Offering) -> QueryOfferingsResult {
+    QueryOfferingsResult {
+        id,
+        token_id: offer.token_id,
+        sale_type: offer.sale_type,
+        royalty_info: offer.royalty_info,
+        nft_address: offer.nft_address.to_string(),
+        seller: offer.seller.to_string(),
+        listing_time: offer.listing_time,
+        denom: offer.denom,
+    }
+}
@@ -1,13 +1,68 @@
-use cosmwasm_std::{Addr, Uint128, Timestamp, Storage, StdResult, Env};
+use cosmwasm_std::{Addr, Uint128, Timestamp, Storage, StdResult, Env, BlockInfo, Decimal, Empty};
 use cw721::Expiration;
-use nft_base::msg::RoyaltyInfoResponse;
+use nft_base::msg::{RoyaltyInfoResponse, RoyaltyRecipientResponse};
 use serde::{Deserialize, Serialize};
 use schemars::JsonSchema;
 use cw_storage_plus::{Item, Map};
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
 pub struct ContractInfo {
     pub name: String,
-    pub native_denom: String
+    pub native_denom: String,
+    /// Marketplace fee taken from every sale and paid to the admin
+    #[serde(default)]
+    pub fee: Decimal,
+    #[serde(default)]
+    pub royalty_policy: RoyaltyPolicy,
+}
+
+/// Which royalty is paid when a sale settles
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RoyaltyPolicy {
+    /// The royalty recorded on the offering when the NFT was listed
+    #[default]
+    Snapshot,
+    /// The royalty the NFT contract reports for the token at settlement time
+    Live,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct CollectionConfig {
+    /// Suspended collections cannot be listed or traded, existing listings can still be withdrawn
+    pub suspended: bool,
+    /// Overrides the marketplace fee for this collection
+    pub fee: Option<Decimal>,
+    pub allow_fixed_price: bool,
+    pub allow_auction: bool,
+    /// Lowest fixed price or auction start price accepted for a listing
+    pub min_price: Option<Uint128>,
+    /// Denoms listings may be priced in, the native denom only when empty
+    pub denoms: Vec<String>,
+    pub enforce_royalties: bool,
+}
+
+impl Default for CollectionConfig {
+    fn default() -> Self {
+        Self {
+            suspended: false,
+            fee: None,
+            allow_fixed_price: true,
+            allow_auction: true,
+            min_price: None,
+            denoms: vec![],
+            enforce_royalties: true,
+        }
+    }
+}
+
+impl CollectionConfig {
+    pub fn accepts_denom(&self, denom: &str, native_denom: &str) -> bool {
+        if self.denoms.is_empty() {
+            denom == native_denom
+        } else {
+            self.denoms.iter().any(|d| d == denom)
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -21,6 +76,18 @@ pub struct Bid {
     pub start_price: Uint128,
     pub increase_per_bid: Option<Uint128>,
     pub expiration: Expiration,
+    /// Any bid of at least this amount ends the auction immediately
+    pub buy_now_price: Option<Uint128>,
+    /// Bids are rejected before this time, defaults to the listing time
+    pub start_time: Option<Timestamp>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum AuctionStatus {
+    Upcoming,
+    Live,
+    Ended,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -31,6 +98,48 @@ pub struct Offering {
     pub seller: Addr,
     pub sale_type: SaleType,
     pub listing_time: Timestamp,
+    /// Denom the listing is priced in, fixed at listing time.
+    /// Empty for listings stored before denoms existed until `migrate` fills it in
+    #[serde(default)]
+    pub denom: String,
+}
+
+/// Offering as stored before royalties were split across recipients
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct LegacyOffering {
+    pub token_id: String,
+    pub nft_address: Addr,
+    pub royalty_info: Option<LegacyRoyaltyInfo>,
+    pub seller: Addr,
+    pub sale_type: SaleType,
+    pub listing_time: Timestamp,
+    #[serde(default)]
+    pub denom: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
+pub struct LegacyRoyaltyInfo {
+    pub payment_address: String,
+    pub share: Decimal,
+}
+
+impl From<LegacyOffering> for Offering {
+    fn from(offer: LegacyOffering) -> Self {
+        Offering {
+            token_id: offer.token_id,
+            nft_address: offer.nft_address,
+            royalty_info: offer.royalty_info.map(|royalty_info| RoyaltyInfoResponse {
+                recipients: vec![RoyaltyRecipientResponse {
+                    payment_address: royalty_info.payment_address,
+                    share: royalty_info.share,
+                }],
+            }),
+            seller: offer.seller,
+            sale_type: offer.sale_type,
+            listing_time: offer.listing_time,
+            denom: offer.denom,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug)]
@@ -41,11 +150,21 @@ pub struct BidOffering{
 }
 
 impl BidOffering {
-    pub fn default(env: &Env)-> Self {
+    pub fn default(env: &Env, start_time: Option<Timestamp>)-> Self {
         Self {
             highest_bid_price: None,
             address: None,
-            start_timestamp: env.block.time
+            start_timestamp: start_time.unwrap_or(env.block.time)
+        }
+    }
+
+    pub fn status(&self, bid: &Bid, block: &BlockInfo) -> AuctionStatus {
+        if bid.expiration.is_expired(block) {
+            AuctionStatus::Ended
+        } else if block.time < self.start_timestamp {
+            AuctionStatus::Upcoming
+        } else {
+            AuctionStatus::Live
         }
     }
 
@@ -59,16 +178,33 @@ impl BidOffering {
     }
 }
 pub const OFFERINGS: Map<&str, Offering> = Map::new("offerings");
+/// Same entries as `OFFERINGS`, for reading offerings `migrate` has not converted yet
+pub const LEGACY_OFFERINGS: Map<&str, LegacyOffering> = Map::new("offerings");
 pub const OFFERINGS_COUNT: Item<u64> = Item::new("num_offerings");
 pub const BID_OFFERINGS: Map<&str, BidOffering> = Map::new("bid_offerings");
-pub const CONTRACT_INFO: Item<ContractInfo> = Item::new("contract_info");
+pub const CONTRACT_INFO: Item<ContractInfo> = Item::new("marketplace_info");
+/// Where older versions kept the config. cw2 stores the contract version under the same key,
+/// so it is only readable if the version was never written over it
+pub const LEGACY_CONTRACT_INFO: Item<ContractInfo> = Item::new("contract_info");
 pub const ADMIN: Item<Addr> = Item::new("admin");
-pub const NFT_CONTRACTS: Item<Vec<Addr>>= Item::new("nft_contracts");
+pub const PENDING_ADMIN: Item<Addr> = Item::new("pending_admin");
+pub const PAUSED: Item<bool> = Item::new("paused");
+pub const NFT_CONTRACTS: Map<&Addr, CollectionConfig> = Map::new("nft_contracts");
+/// Whitelist of older versions, moved into `NFT_CONTRACTS` by `migrate`
+pub const LEGACY_NFT_CONTRACTS: Item<Vec<Addr>> = Item::new("nft_contracts");
+/// Contracts instantiated from these code ids are whitelisted on their first listing
+pub const TRUSTED_CODE_IDS: Map<u64, Empty> = Map::new("trusted_code_ids");
+/// Collections removed by the admin, never registered again through a trusted code id
+pub const REMOVED_NFT_CONTRACTS: Map<&Addr, Empty> = Map::new("removed_nft_contracts");
 
 pub fn num_offerings(storage: &dyn Storage) -> StdResult<u64> {
     Ok(OFFERINGS_COUNT.may_load(storage)?.unwrap_or_default())
 }
 
+pub fn is_paused(storage: &dyn Storage) -> StdResult<bool> {
+    Ok(PAUSED.may_load(storage)?.unwrap_or_default())
+}
+
 pub fn increment_offerings(storage: &mut dyn Storage) -> StdResult<u64> {
     let val = num_offerings(storage)? + 1;
     OFFERINGS_COUNT.save(storage, &val)?;